@@ -333,7 +333,7 @@ pub fn input_box(input: &InputBox) -> Option<String> {
 
 pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
     let title = dialog.dialog.title();
-    let path = dialog.path();
+    let path = dialog.initial_path();
     let filter_patterns = dialog.filter_patterns();
     
     if !init_jni() {
@@ -396,7 +396,7 @@ pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
 
 pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
     let title = dialog.dialog.title();
-    let path = dialog.path();
+    let path = dialog.initial_path();
     let filter_patterns = dialog.filter_patterns();
     let allow_multi = dialog.multiple_selection();
     
@@ -477,7 +477,7 @@ pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
 
 pub fn select_folder_dialog(dialog: &FileDialog) -> Option<String> {
     let title = dialog.dialog.title();
-    let path = dialog.path();
+    let path = dialog.initial_path();
     
     if !init_jni() {
         return None;
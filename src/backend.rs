@@ -0,0 +1,442 @@
+use super::*;
+use crate::config::Config;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// A way of showing dialogs, such as zenity, kdialog or the Win32 API.
+///
+/// Every platform registers its built-in backends on first use. Applications
+/// can add their own with [`register_backend`] and pick one by name with
+/// [`set_backend`].
+pub trait DialogBackend: Send + Sync {
+    /// Name used to select this backend with [`set_backend`].
+    fn name(&self) -> &str;
+
+    /// Whether the backend can be used on this machine. Automatic selection
+    /// skips backends that report `false`.
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// Whether dialogs are drawn on the terminal rather than in a window.
+    /// The user configuration can force or forbid such backends.
+    fn uses_terminal(&self) -> bool {
+        false
+    }
+
+    /// Version of the program or library behind the backend, if known.
+    fn version(&self) -> Option<String> {
+        None
+    }
+
+    /// What the backend can show by itself. Defaults to everything,
+    /// backends that fall back to the terminal or leave features out should
+    /// say so.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all()
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError>;
+
+    /// Asks to confirm or cancel. This and the other fixed button sets
+    /// focus the button of `default` where the program allows it. kdialog
+    /// has no such option and always focuses its first button, Ok or Yes.
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError>;
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError>;
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError>;
+
+    /// Shows [`MessageBox::buttons`] and returns the index of the pressed
+    /// one. The default implementation falls back to the fixed button sets,
+    /// losing the labels, and supports at most three buttons.
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        emulate_buttons(self, msg_box)
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError>;
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError>;
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError>;
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError>;
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError>;
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError>;
+
+    // Backends whose dialogs can be awaited without blocking a thread
+    #[doc(hidden)]
+    fn as_async(&self) -> Option<&dyn AsyncDialogs> {
+        None
+    }
+}
+
+pub(crate) type DialogResult<'a, T> =
+    Pin<Box<dyn Future<Output = Result<T, DialogError>> + Send + 'a>>;
+
+// The dialogs of `DialogBackend` as futures, for the `*_async` methods. Not
+// reachable from outside the crate, so only built-in backends implement it.
+// Timeouts are up to the backend.
+pub trait AsyncDialogs: Send + Sync {
+    fn message_box_ok<'a>(&'a self, msg_box: &'a MessageBox) -> DialogResult<'a, ()>;
+
+    fn message_box_ok_cancel<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: OkCancel,
+    ) -> DialogResult<'a, OkCancel>;
+
+    fn message_box_yes_no<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: YesNo,
+    ) -> DialogResult<'a, YesNo>;
+
+    fn message_box_yes_no_cancel<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: YesNoCancel,
+    ) -> DialogResult<'a, YesNoCancel>;
+
+    fn message_box_buttons<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+    ) -> DialogResult<'a, Option<usize>>;
+
+    fn input_box<'a>(&'a self, input: &'a InputBox) -> DialogResult<'a, Option<String>>;
+
+    fn save_file_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<SaveResult>>;
+
+    fn open_file_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<Vec<PathBuf>>>;
+
+    fn select_folder_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<PathBuf>>;
+
+    fn color_chooser_dialog<'a>(
+        &'a self,
+        chooser: &'a ColorChooser,
+    ) -> DialogResult<'a, Option<(String, [u8; 3])>>;
+
+    fn notification<'a>(&'a self, notification: &'a Notification) -> DialogResult<'a, ()>;
+}
+
+/// What a backend can show by itself, see [`DialogBackend::capabilities`].
+///
+/// Dialogs a backend lacks still work but are degraded, e.g. asked on the
+/// terminal instead or shown without the feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Input boxes with a text field.
+    pub input_box: bool,
+    /// File and folder dialogs to browse with.
+    pub file_dialogs: bool,
+    /// Message boxes with Yes, No and Cancel.
+    pub yes_no_cancel: bool,
+    /// Picking several files in one open dialog.
+    pub multiple_selection: bool,
+    /// A graphical color picker.
+    pub color_chooser: bool,
+    /// Input boxes that hide what is typed.
+    pub password: bool,
+    /// File dialogs that only list files matching the filters.
+    pub filters: bool,
+    /// Desktop notifications.
+    pub notifications: bool,
+    /// Desktop notifications with buttons.
+    pub notification_actions: bool,
+    /// Dialogs that close themselves when their timeout runs out. Without
+    /// this they are closed from outside a moment later, or not at all on
+    /// the terminal.
+    pub timeout: bool,
+    /// Dialogs kept above the window given to `with_parent_window`.
+    pub parent_window: bool,
+}
+
+impl Capabilities {
+    /// Everything supported.
+    pub fn all() -> Self {
+        Self {
+            input_box: true,
+            file_dialogs: true,
+            yes_no_cancel: true,
+            multiple_selection: true,
+            color_chooser: true,
+            password: true,
+            filters: true,
+            notifications: true,
+            notification_actions: true,
+            timeout: true,
+            parent_window: true,
+        }
+    }
+}
+
+/// Describes a backend, see [`backend_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BackendInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub capabilities: Capabilities,
+}
+
+// Custom buttons on top of the fixed Ok, Ok/Cancel and Yes/No/Cancel boxes
+pub(crate) fn emulate_buttons<B: DialogBackend + ?Sized>(
+    backend: &B,
+    msg_box: &MessageBox,
+) -> Result<Option<usize>, DialogError> {
+    match msg_box.buttons().len() {
+        0 | 1 => backend.message_box_ok(msg_box).map(|()| Some(0)),
+        2 => Ok(Some(
+            match backend.message_box_ok_cancel(msg_box, OkCancel::Ok)? {
+                OkCancel::Ok => 0,
+                OkCancel::Cancel => 1,
+            },
+        )),
+        3 => Ok(Some(
+            match backend.message_box_yes_no_cancel(msg_box, YesNoCancel::Yes)? {
+                YesNoCancel::Yes => 0,
+                YesNoCancel::No => 1,
+                YesNoCancel::Cancel => 2,
+            },
+        )),
+        n => Err(DialogError::Unsupported(format!(
+            "{} buttons in a message box",
+            n
+        ))),
+    }
+}
+
+struct Registry {
+    // Ordered by preference, most preferred first
+    backends: Vec<Arc<dyn DialogBackend>>,
+    // Names of the backends added with `register_backend`
+    registered: Vec<String>,
+    selected: Option<String>,
+    config: Config,
+}
+
+fn builtin_backends() -> Vec<Arc<dyn DialogBackend>> {
+    #[cfg(target_os = "macos")]
+    return macos::builtin_backends();
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    return unix::builtin_backends();
+
+    #[cfg(target_os = "windows")]
+    return windows::builtin_backends();
+
+    #[allow(unreachable_code)]
+    Vec::new()
+}
+
+thread_local! {
+    // Overrides the registry for one thread, see `testing::MockBackend`
+    static THREAD_BACKEND: RefCell<Option<Arc<dyn DialogBackend>>> = const { RefCell::new(None) };
+}
+
+// Returns the previous thread backend so it can be restored
+pub(crate) fn set_thread_backend(
+    backend: Option<Arc<dyn DialogBackend>>,
+) -> Option<Arc<dyn DialogBackend>> {
+    THREAD_BACKEND.with(|cell| cell.replace(backend))
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        RwLock::new(Registry {
+            backends: builtin_backends(),
+            registered: Vec::new(),
+            selected: None,
+            config: Config::load(),
+        })
+    })
+}
+
+/// Adds a backend in front of the built-in ones, replacing any backend
+/// registered under the same name.
+pub fn register_backend(backend: Arc<dyn DialogBackend>) {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry.backends.retain(|b| b.name() != backend.name());
+    registry.registered.retain(|name| name != backend.name());
+    registry.registered.push(backend.name().to_string());
+    registry.backends.insert(0, backend);
+}
+
+/// Detects the installed dialog programs, the desktop session and the
+/// user's preferences again.
+///
+/// All of these are looked up once and cached for the life of the process.
+/// Long-running processes can call this after programs were installed or
+/// the environment changed. Registered backends and the backend chosen
+/// with [`set_backend`] are kept.
+pub fn refresh_backends() {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    unix::forget_programs();
+
+    let builtins = builtin_backends();
+    let config = Config::load();
+
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    let registered = registry.registered.clone();
+    registry
+        .backends
+        .retain(|b| registered.iter().any(|name| name == b.name()));
+    registry.backends.extend(
+        builtins
+            .into_iter()
+            .filter(|b| !registered.iter().any(|name| name == b.name())),
+    );
+    registry.config = config;
+}
+
+/// Uses the backend called `name` for all following dialogs, whether or not
+/// it reports itself as available. This takes precedence over the user's
+/// `TFD_BACKEND` and config file.
+pub fn set_backend(name: &str) -> Result<(), DialogError> {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    if !registry.backends.iter().any(|b| b.name() == name) {
+        return Err(DialogError::UnknownBackend(name.to_string()));
+    }
+    registry.selected = Some(name.to_string());
+    Ok(())
+}
+
+/// Goes back to picking the first available backend for each dialog,
+/// honouring the user's preferences.
+pub fn unset_backend() {
+    let mut registry = registry().write().unwrap_or_else(|e| e.into_inner());
+    registry.selected = None;
+}
+
+/// Names of all registered backends, most preferred first.
+pub fn backend_names() -> Vec<String> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    registry
+        .backends
+        .iter()
+        .map(|b| b.name().to_string())
+        .collect()
+}
+
+/// Describes the backend the next dialog will be shown with.
+///
+/// Looking up the version may run the dialog program.
+pub fn backend_info() -> Result<BackendInfo, DialogError> {
+    let backend = current_backend()?;
+    Ok(BackendInfo {
+        name: backend.name().to_string(),
+        version: backend.version(),
+        capabilities: backend.capabilities(),
+    })
+}
+
+/// The backend the next dialog will be shown with.
+pub fn current_backend() -> Result<Arc<dyn DialogBackend>, DialogError> {
+    if let Some(backend) = THREAD_BACKEND.with(|cell| cell.borrow().clone()) {
+        return Ok(backend);
+    }
+
+    let (backends, selected, config) = {
+        let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+        (
+            registry.backends.clone(),
+            registry.selected.clone(),
+            registry.config.clone(),
+        )
+    };
+
+    // Availability checks may spawn processes, so they run without the lock
+    match selected {
+        Some(name) => backends
+            .into_iter()
+            .find(|b| b.name() == name)
+            .ok_or(DialogError::UnknownBackend(name)),
+        None => candidates(backends, &config)
+            .into_iter()
+            .find(|b| b.is_available())
+            .ok_or(DialogError::NoBackend),
+    }
+}
+
+// The backend automatic selection picks when `name` is left out, for
+// backends that hand the dialogs they cannot show on to the next one
+#[cfg(all(unix, not(target_os = "macos"), feature = "portal"))]
+pub(crate) fn fallback_backend(name: &str) -> Result<Arc<dyn DialogBackend>, DialogError> {
+    let (backends, config) = {
+        let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+        (registry.backends.clone(), registry.config.clone())
+    };
+    fallback_from(backends, &config, name)
+}
+
+// The first available one of `backends` other than `name`, in the order
+// automatic selection tries them
+#[cfg(all(unix, not(target_os = "macos"), feature = "portal"))]
+pub(crate) fn fallback_from(
+    backends: Vec<Arc<dyn DialogBackend>>,
+    config: &Config,
+    name: &str,
+) -> Result<Arc<dyn DialogBackend>, DialogError> {
+    candidates(backends, config)
+        .into_iter()
+        .find(|b| b.name() != name && b.is_available())
+        .ok_or(DialogError::NoBackend)
+}
+
+// Backends automatic selection may pick, in the order they are tried: the
+// user's preferred ones first, then the rest in registration order
+fn candidates(
+    backends: Vec<Arc<dyn DialogBackend>>,
+    config: &Config,
+) -> Vec<Arc<dyn DialogBackend>> {
+    let preference = |backend: &Arc<dyn DialogBackend>| {
+        config
+            .backends
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(backend.name()))
+            .unwrap_or(usize::MAX)
+    };
+
+    let mut backends = backends;
+    // Stable, so unlisted backends keep their order
+    backends.sort_by_key(preference);
+
+    // Forcing the console means nothing on platforms without a terminal
+    // backend, forcing wins when both flags are set
+    if config.force_console && backends.iter().any(|b| b.uses_terminal()) {
+        backends.retain(|b| b.uses_terminal());
+    } else if config.forbid_console {
+        backends.retain(|b| !b.uses_terminal());
+    }
+    backends
+}
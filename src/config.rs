@@ -0,0 +1,215 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+// What the user asked for in TFD_BACKEND and the config file, consulted when
+// the application has not picked a backend itself.
+//
+// The file is `$XDG_CONFIG_HOME/tfd/config.toml`, falling back to
+// `~/.config/tfd/config.toml`, and only needs a few top-level keys:
+//
+//     backends = ["kdialog", "zenity"]
+//     force_console = false
+//     forbid_console = false
+//
+// TFD_BACKEND holds a comma separated list of names and replaces `backends`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Config {
+    // Backend names to try first, most preferred first
+    pub backends: Vec<String>,
+    // Only use backends that draw on the terminal, like `tinyfd_forceConsole`
+    pub force_console: bool,
+    // Never fall back to the terminal
+    pub forbid_console: bool,
+}
+
+impl Config {
+    pub(crate) fn load() -> Self {
+        let mut config = config_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default();
+
+        if let Ok(names) = env::var("TFD_BACKEND") {
+            config.backends = split_names(&names);
+        }
+        config
+    }
+
+    // Reads the keys above and skips everything it does not understand, a
+    // broken config file should not keep dialogs from showing
+    fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        let mut lines = text.lines().map(strip_comment);
+
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                // Everything after a table header belongs to that table
+                break;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let mut value = value.trim().to_string();
+            if value.starts_with('[') {
+                while !value.ends_with(']') {
+                    match lines.next() {
+                        Some(more) => value.push_str(more.trim()),
+                        None => break,
+                    }
+                }
+            }
+
+            match (key.trim(), value.as_str()) {
+                ("backends", value) => {
+                    if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                        config.backends = list.split(',').filter_map(unquote).collect();
+                    }
+                }
+                ("force_console", "true") => config.force_console = true,
+                ("force_console", "false") => config.force_console = false,
+                ("forbid_console", "true") => config.forbid_console = true,
+                ("forbid_console", "false") => config.forbid_console = false,
+                _ => {}
+            }
+        }
+        config
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("tfd").join("config.toml"))
+}
+
+fn split_names(names: &str) -> Vec<String> {
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// Cuts a `#` comment that is not inside a string
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(item: &str) -> Option<String> {
+    let item = item.trim();
+    let inner = item
+        .strip_prefix('"')
+        .and_then(|i| i.strip_suffix('"'))
+        .or_else(|| item.strip_prefix('\'').and_then(|i| i.strip_suffix('\'')))?;
+    Some(inner.to_string()).filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(config: &Config) -> Vec<&str> {
+        config.backends.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn backends_array() {
+        let config = Config::parse("backends = [\"kdialog\", 'zenity',\"\" , yad]\n");
+        // Unquoted and empty names are dropped
+        assert_eq!(names(&config), ["kdialog", "zenity"]);
+
+        let config = Config::parse("backends = [\n  \"kdialog\",\n  \"zenity\", # second\n]\n");
+        assert_eq!(names(&config), ["kdialog", "zenity"]);
+    }
+
+    #[test]
+    fn comments() {
+        let config = Config::parse(
+            "# preferred dialogs\n\
+             backends = [\"zenity\"] # the rest are tried afterwards\n\
+             force_console = true # only on the terminal\n\
+             #forbid_console = true\n",
+        );
+        assert_eq!(names(&config), ["zenity"]);
+        assert!(config.force_console);
+        assert!(!config.forbid_console);
+    }
+
+    #[test]
+    fn quoted_hash() {
+        let config = Config::parse("backends = [\"#kdialog\", 'zen#ity'] # comment\n");
+        assert_eq!(names(&config), ["#kdialog", "zen#ity"]);
+        assert_eq!(strip_comment("a = \"#\" # b"), "a = \"#\" ");
+    }
+
+    #[test]
+    fn console_flags() {
+        let config = Config::parse("force_console = true\nforbid_console = true\n");
+        assert!(config.force_console);
+        assert!(config.forbid_console);
+
+        // The last value wins
+        let config = Config::parse("force_console = true\nforce_console = false\n");
+        assert!(!config.force_console);
+
+        let config = Config::parse("  forbid_console=true  \n");
+        assert!(config.forbid_console);
+    }
+
+    #[test]
+    fn malformed_lines_are_ignored() {
+        let config = Config::parse(
+            "this is not toml\n\
+             force_console = yes\n\
+             forbid_console = \"true\"\n\
+             backends = \"zenity\"\n\
+             unknown = 1\n\
+             = true\n\
+             backends = [\"kdialog\"]\n",
+        );
+        assert_eq!(names(&config), ["kdialog"]);
+        assert!(!config.force_console);
+        assert!(!config.forbid_console);
+
+        // An unclosed array takes the rest of the file with it
+        let config = Config::parse("backends = [\"kdialog\",\nforce_console = true\n");
+        assert!(config.backends.is_empty());
+        assert!(!config.force_console);
+
+        assert_eq!(Config::parse(""), Config::default());
+    }
+
+    #[test]
+    fn tables_end_the_top_level() {
+        let config = Config::parse(
+            "force_console = true\n\
+             [zenity]\n\
+             forbid_console = true\n\
+             backends = [\"zenity\"]\n",
+        );
+        assert!(config.force_console);
+        assert!(!config.forbid_console);
+        assert!(config.backends.is_empty());
+    }
+
+    #[test]
+    fn backend_variable() {
+        assert_eq!(split_names(" kdialog, ,zenity ,"), ["kdialog", "zenity"]);
+        assert!(split_names("").is_empty());
+    }
+}
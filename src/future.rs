@@ -0,0 +1,331 @@
+use super::*;
+use crate::handle::CancelToken;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+type Job<T> = Box<dyn FnOnce() -> Result<T, DialogError> + Send>;
+
+/// A dialog running in the background, returned by the `*_async` methods.
+///
+/// Dialog programs such as zenity or kdialog are awaited like any other
+/// future: their exit is picked up through a pidfd or SIGCHLD and their
+/// answer is read without blocking, so no thread waits for them. Native
+/// dialogs, the terminal and the macOS and Windows backends block whoever
+/// shows them; those run on a helper thread started when the future is
+/// first polled. With the `tokio` feature enabled, tokio's blocking pool is
+/// used for them instead when polled inside a runtime.
+///
+/// The future works with any executor and is `'static`, so it can be spawned.
+/// Dropping it before it completes closes the dialog like
+/// [`DialogHandle::close`].
+#[must_use = "futures do nothing unless polled"]
+pub struct DialogFuture<T> {
+    dialog: Pin<Box<dyn Future<Output = Result<T, DialogError>> + Send>>,
+}
+
+impl<T: Send + 'static> DialogFuture<T> {
+    // Binds the dialog to the backend that is current on the calling thread
+    pub(crate) fn spawn<F, D>(show: F) -> Self
+    where
+        F: FnOnce(AsyncBackend) -> D,
+        D: Future<Output = Result<T, DialogError>> + Send + 'static,
+    {
+        let dialog: Pin<Box<dyn Future<Output = _> + Send>> = match current_backend() {
+            Ok(backend) => Box::pin(show(AsyncBackend(backend))),
+            Err(err) => Box::pin(std::future::ready(Err(err))),
+        };
+        Self { dialog }
+    }
+}
+
+impl<T> Future for DialogFuture<T> {
+    type Output = Result<T, DialogError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.dialog.as_mut().poll(cx)
+    }
+}
+
+// The backend a `DialogFuture` is bound to. Dialogs the backend can await
+// are awaited, the others are shown on a helper thread.
+pub(crate) struct AsyncBackend(Arc<dyn DialogBackend>);
+
+impl AsyncBackend {
+    fn blocking<T, F>(&self, show: F) -> Blocking<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn DialogBackend) -> Result<T, DialogError> + Send + 'static,
+    {
+        let backend = self.0.clone();
+        Blocking {
+            state: State::Idle(Box::new(move || show(backend.as_ref()))),
+            token: CancelToken::new(),
+        }
+    }
+
+    pub(crate) async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.message_box_ok(msg_box).await;
+        }
+        let msg_box = msg_box.clone();
+        self.blocking(move |backend| msg_box.dialog.show(|| backend.message_box_ok(&msg_box)))
+            .await
+    }
+
+    pub(crate) async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.message_box_ok_cancel(msg_box, default).await;
+        }
+        let msg_box = msg_box.clone();
+        self.blocking(move |backend| {
+            msg_box
+                .dialog
+                .show(|| backend.message_box_ok_cancel(&msg_box, default))
+        })
+        .await
+    }
+
+    pub(crate) async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.message_box_yes_no(msg_box, default).await;
+        }
+        let msg_box = msg_box.clone();
+        self.blocking(move |backend| {
+            msg_box
+                .dialog
+                .show(|| backend.message_box_yes_no(&msg_box, default))
+        })
+        .await
+    }
+
+    pub(crate) async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.message_box_yes_no_cancel(msg_box, default).await;
+        }
+        let msg_box = msg_box.clone();
+        self.blocking(move |backend| {
+            msg_box
+                .dialog
+                .show(|| backend.message_box_yes_no_cancel(&msg_box, default))
+        })
+        .await
+    }
+
+    pub(crate) async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.message_box_buttons(msg_box).await;
+        }
+        let msg_box = msg_box.clone();
+        self.blocking(move |backend| {
+            msg_box
+                .dialog
+                .show(|| backend.message_box_buttons(&msg_box))
+        })
+        .await
+    }
+
+    pub(crate) async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.input_box(input).await;
+        }
+        let input = input.clone();
+        self.blocking(move |backend| input.dialog.show(|| backend.input_box(&input)))
+            .await
+    }
+
+    pub(crate) async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.save_file_dialog(dialog).await;
+        }
+        let dialog = dialog.clone();
+        self.blocking(move |backend| dialog.dialog.show(|| backend.save_file_dialog(&dialog)))
+            .await
+    }
+
+    pub(crate) async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.open_file_dialog(dialog).await;
+        }
+        let dialog = dialog.clone();
+        self.blocking(move |backend| dialog.dialog.show(|| backend.open_file_dialog(&dialog)))
+            .await
+    }
+
+    pub(crate) async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.select_folder_dialog(dialog).await;
+        }
+        let dialog = dialog.clone();
+        self.blocking(move |backend| dialog.dialog.show(|| backend.select_folder_dialog(&dialog)))
+            .await
+    }
+
+    pub(crate) async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.color_chooser_dialog(chooser).await;
+        }
+        let chooser = chooser.clone();
+        self.blocking(move |backend| backend.color_chooser_dialog(&chooser))
+            .await
+    }
+
+    pub(crate) async fn notification(
+        &self,
+        notification: &Notification,
+    ) -> Result<(), DialogError> {
+        if let Some(dialogs) = self.0.as_async() {
+            return dialogs.notification(notification).await;
+        }
+        let notification = notification.clone();
+        self.blocking(move |backend| backend.notification(&notification))
+            .await
+    }
+}
+
+// A dialog of a backend that blocks the thread showing it. It is started
+// on a helper thread when first polled and closed when dropped.
+struct Blocking<T> {
+    state: State<T>,
+    token: CancelToken,
+}
+
+enum State<T> {
+    Idle(Job<T>),
+    Running(Arc<Slot<T>>),
+    #[cfg(feature = "tokio")]
+    Tokio(tokio::task::JoinHandle<Result<T, DialogError>>),
+    Done,
+}
+
+// Where the helper thread leaves the result for the future to pick up
+struct Slot<T> {
+    inner: Mutex<SlotInner<T>>,
+}
+
+struct SlotInner<T> {
+    result: Option<thread::Result<Result<T, DialogError>>>,
+    waker: Option<Waker>,
+}
+
+impl<T: Send + 'static> Blocking<T> {
+    fn start(&self, job: Job<T>) -> State<T> {
+        let token = self.token.clone();
+        let job = move || token.run(job);
+
+        #[cfg(feature = "tokio")]
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            return State::Tokio(runtime.spawn_blocking(job));
+        }
+
+        let slot = Arc::new(Slot {
+            inner: Mutex::new(SlotInner {
+                result: None,
+                waker: None,
+            }),
+        });
+
+        let worker_slot = slot.clone();
+        thread::spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            let waker = {
+                let mut inner = worker_slot.inner.lock().unwrap_or_else(|e| e.into_inner());
+                inner.result = Some(result);
+                inner.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+
+        State::Running(slot)
+    }
+}
+
+impl<T: Send + 'static> Future for Blocking<T> {
+    type Output = Result<T, DialogError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if let State::Idle(_) = this.state {
+            if let State::Idle(job) = std::mem::replace(&mut this.state, State::Done) {
+                this.state = this.start(job);
+            }
+        }
+
+        match &mut this.state {
+            State::Idle(_) => unreachable!(),
+            State::Running(slot) => {
+                let mut inner = slot.inner.lock().unwrap_or_else(|e| e.into_inner());
+                match inner.result.take() {
+                    Some(result) => {
+                        drop(inner);
+                        this.state = State::Done;
+                        match result {
+                            Ok(result) => Poll::Ready(result),
+                            Err(payload) => panic::resume_unwind(payload),
+                        }
+                    }
+                    None => {
+                        match &inner.waker {
+                            Some(waker) if waker.will_wake(cx.waker()) => {}
+                            _ => inner.waker = Some(cx.waker().clone()),
+                        }
+                        Poll::Pending
+                    }
+                }
+            }
+            #[cfg(feature = "tokio")]
+            State::Tokio(handle) => match Pin::new(handle).poll(cx) {
+                Poll::Ready(result) => {
+                    this.state = State::Done;
+                    match result {
+                        Ok(result) => Poll::Ready(result),
+                        Err(err) if err.is_panic() => panic::resume_unwind(err.into_panic()),
+                        // The runtime shut down before the dialog was shown
+                        Err(_) => Poll::Ready(Err(DialogError::Closed)),
+                    }
+                }
+                Poll::Pending => Poll::Pending,
+            },
+            State::Done => panic!("dialog polled after completion"),
+        }
+    }
+}
+
+impl<T> Drop for Blocking<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
@@ -0,0 +1,272 @@
+use super::*;
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type Answer<T> = thread::Result<Result<T, DialogError>>;
+
+/// A dialog shown on a background thread, returned by the `spawn*` methods.
+///
+/// Closing or dropping the handle dismisses the dialog: dialog programs are
+/// killed and native dialogs are sent a close request. Backends that read
+/// from the terminal cannot be interrupted, closing them only abandons the
+/// answer.
+#[must_use = "dropping a DialogHandle closes the dialog"]
+pub struct DialogHandle<T> {
+    token: CancelToken,
+    answer: Receiver<Answer<T>>,
+}
+
+impl<T: Send + 'static> DialogHandle<T> {
+    pub(crate) fn spawn<F>(show: F) -> Self
+    where
+        F: FnOnce(&dyn DialogBackend) -> Result<T, DialogError> + Send + 'static,
+    {
+        let token = CancelToken::new();
+        let (sender, answer) = mpsc::channel();
+
+        match current_backend() {
+            Ok(backend) => {
+                let worker_token = token.clone();
+                thread::spawn(move || {
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                        worker_token.run(|| show(backend.as_ref()))
+                    }));
+                    let _ = sender.send(result);
+                });
+            }
+            Err(err) => {
+                let _ = sender.send(Ok(Err(err)));
+            }
+        }
+
+        Self { token, answer }
+    }
+
+    /// Returns the answer if the dialog has been dismissed, without blocking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called again after the answer was returned.
+    pub fn try_wait(&mut self) -> Result<Option<T>, DialogError> {
+        match self.answer.try_recv() {
+            Ok(answer) => unwrap_answer(answer).map(Some),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => {
+                panic!("DialogHandle::try_wait called after the dialog finished")
+            }
+        }
+    }
+
+    /// Blocks until the dialog is dismissed and returns the answer.
+    pub fn wait(self) -> Result<T, DialogError> {
+        match self.answer.recv() {
+            Ok(answer) => unwrap_answer(answer),
+            Err(_) => panic!("DialogHandle::wait called after the dialog finished"),
+        }
+    }
+
+    /// Dismisses the dialog without waiting for an answer.
+    pub fn close(self) {
+        self.token.cancel();
+    }
+}
+
+impl<T> Drop for DialogHandle<T> {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+fn unwrap_answer<T>(answer: Answer<T>) -> Result<T, DialogError> {
+    match answer {
+        Ok(result) => result,
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+thread_local! {
+    static CURRENT_TOKEN: RefCell<Option<CancelToken>> = const { RefCell::new(None) };
+}
+
+type Hook = Box<dyn Fn() + Send>;
+
+// Why a token was cancelled, first one wins
+const RUNNING: u8 = 0;
+const CLOSED: u8 = 1;
+const TIMED_OUT: u8 = 2;
+
+// Head start for dialog programs that time out by themselves before the
+// watchdog kills them
+pub(crate) const WATCHDOG_GRACE: Duration = Duration::from_secs(1);
+
+// Shared between whoever may close a dialog and the thread showing it
+#[derive(Clone)]
+pub(crate) struct CancelToken {
+    inner: Arc<TokenInner>,
+}
+
+struct TokenInner {
+    state: AtomicU8,
+    hook: Mutex<Option<Hook>>,
+}
+
+impl CancelToken {
+    pub(crate) fn new() -> Self {
+        Self {
+            inner: Arc::new(TokenInner {
+                state: AtomicU8::new(RUNNING),
+                hook: Mutex::new(None),
+            }),
+        }
+    }
+
+    pub(crate) fn cancel(&self) {
+        self.cancel_with(CLOSED);
+    }
+
+    fn cancel_with(&self, reason: u8) {
+        let hook = self.inner.hook.lock().unwrap_or_else(|e| e.into_inner());
+        let cancelled =
+            self.inner
+                .state
+                .compare_exchange(RUNNING, reason, Ordering::SeqCst, Ordering::SeqCst);
+        if cancelled.is_ok() {
+            if let Some(hook) = hook.as_ref() {
+                hook();
+            }
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.inner.state.load(Ordering::SeqCst) != RUNNING
+    }
+
+    fn error(&self) -> Option<DialogError> {
+        match self.inner.state.load(Ordering::SeqCst) {
+            RUNNING => None,
+            TIMED_OUT => Some(DialogError::TimedOut),
+            _ => Some(DialogError::Closed),
+        }
+    }
+
+    // Runs a dialog on this thread so that cancelling the token dismisses it
+    pub(crate) fn run<T>(
+        &self,
+        show: impl FnOnce() -> Result<T, DialogError>,
+    ) -> Result<T, DialogError> {
+        let previous = CURRENT_TOKEN.with(|t| t.replace(Some(self.clone())));
+        let result = show();
+        CURRENT_TOKEN.with(|t| *t.borrow_mut() = previous);
+
+        match self.error() {
+            Some(err) => Err(err),
+            None => result,
+        }
+    }
+}
+
+// Runs a dialog on this thread and dismisses it like a closed handle once
+// `timeout` has passed
+pub(crate) fn with_timeout<T>(
+    timeout: Option<Duration>,
+    show: impl FnOnce() -> Result<T, DialogError>,
+) -> Result<T, DialogError> {
+    let Some(timeout) = timeout else {
+        return show();
+    };
+
+    let watch = |token: &CancelToken| {
+        let (done, answered) = mpsc::channel::<()>();
+        let token = token.clone();
+        thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = answered.recv_timeout(timeout + WATCHDOG_GRACE)
+            {
+                token.cancel_with(TIMED_OUT);
+            }
+        });
+        let result = show();
+        drop(done);
+        result
+    };
+
+    match CURRENT_TOKEN.with(|t| t.borrow().clone()) {
+        // The handle or future running the dialog reports the timeout
+        Some(token) => watch(&token),
+        None => {
+            let token = CancelToken::new();
+            token.run(|| watch(&token))
+        }
+    }
+}
+
+/// Unregisters the hook installed by [`on_cancel`] when dropped.
+pub(crate) struct CancelHook {
+    token: Option<CancelToken>,
+}
+
+impl Drop for CancelHook {
+    fn drop(&mut self) {
+        if let Some(token) = &self.token {
+            *token.inner.hook.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        }
+    }
+}
+
+// Calls `hook` from the closing thread if the dialog shown on this thread is
+// closed through its handle or times out. Runs it right away if that already
+// happened.
+pub(crate) fn on_cancel(hook: impl Fn() + Send + 'static) -> CancelHook {
+    let token = CURRENT_TOKEN.with(|t| t.borrow().clone());
+    if let Some(token) = &token {
+        let mut slot = token.inner.hook.lock().unwrap_or_else(|e| e.into_inner());
+        if token.is_cancelled() {
+            hook();
+        } else {
+            *slot = Some(Box::new(hook));
+        }
+    }
+    CancelHook { token }
+}
+
+// Drives a dialog future on this thread. Closing the dialog through its
+// handle or a timeout drops the future, which kills the dialog program.
+#[cfg(all(unix, not(any(target_os = "android", target_os = "ios"))))]
+pub(crate) fn block_on<T>(
+    dialog: impl std::future::Future<Output = Result<T, DialogError>>,
+) -> Result<T, DialogError> {
+    let cancelled = Arc::new(Mutex::new(Cancelled::default()));
+    let _hook = on_cancel({
+        let cancelled = cancelled.clone();
+        move || {
+            let mut cancelled = cancelled.lock().unwrap_or_else(|e| e.into_inner());
+            cancelled.done = true;
+            if let Some(waker) = cancelled.waker.take() {
+                waker.wake();
+            }
+        }
+    });
+
+    // The token tells whether the dialog was closed or timed out
+    let closed = std::future::poll_fn(|cx| {
+        let mut cancelled = cancelled.lock().unwrap_or_else(|e| e.into_inner());
+        if cancelled.done {
+            return std::task::Poll::Ready(Err(DialogError::Closed));
+        }
+        cancelled.waker = Some(cx.waker().clone());
+        std::task::Poll::Pending
+    });
+
+    async_io::block_on(futures_lite::future::or(dialog, closed))
+}
+
+#[cfg(all(unix, not(any(target_os = "android", target_os = "ios"))))]
+#[derive(Default)]
+struct Cancelled {
+    done: bool,
+    waker: Option<std::task::Waker>,
+}
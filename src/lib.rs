@@ -9,19 +9,35 @@
 //! untrusted input, for example as dialog title or message, can in the worst
 //! case lead to execution of arbitrary commands.
 
+use std::fmt;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+mod backend;
+mod config;
+mod future;
+mod handle;
+pub mod testing;
+
+pub use backend::{
+    backend_info, backend_names, current_backend, refresh_backends, register_backend, set_backend,
+    unset_backend, BackendInfo, Capabilities, DialogBackend,
+};
+pub use future::DialogFuture;
+pub use handle::DialogHandle;
 
 // Platform-specific modules
+#[cfg(target_os = "android")]
+mod android;
+#[cfg(target_os = "ios")]
+mod ios;
 #[cfg(target_os = "macos")]
 mod macos;
 #[cfg(all(unix, not(target_os = "macos")))]
 mod unix;
 #[cfg(target_os = "windows")]
 mod windows;
-#[cfg(target_os = "ios")]
-mod ios;
-#[cfg(target_os = "android")]
-mod android;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MessageBoxIcon {
@@ -32,8 +48,8 @@ pub enum MessageBoxIcon {
 }
 
 impl MessageBoxIcon {
-    fn to_str(&self) -> &'static str {
-        match *self {
+    fn to_str(self) -> &'static str {
+        match self {
             MessageBoxIcon::Info => "info",
             MessageBoxIcon::Warning => "warning",
             MessageBoxIcon::Error => "error",
@@ -61,10 +77,124 @@ pub enum YesNoCancel {
     No = 2,
 }
 
+/// Reason why a dialog could not be shown or its answer could not be read.
+///
+/// A user dismissing a dialog is not an error: the `try_*` methods report
+/// that as `Ok(None)` (or as the `Cancel`/`No` variant for message boxes).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DialogError {
+    /// No dialog backend exists for this platform.
+    NoBackend,
+    /// No backend is registered under this name.
+    UnknownBackend(String),
+    /// The dialog program is not installed.
+    NotInstalled(String),
+    /// The dialog program exists but could not be started.
+    Spawn { program: String, source: io::Error },
+    /// A graphical backend was selected but no display is reachable.
+    NoDisplay,
+    /// The dialog program reported an error instead of an answer.
+    Failed {
+        program: String,
+        code: Option<i32>,
+        message: String,
+    },
+    /// The dialog program answered with something that could not be parsed.
+    InvalidOutput { program: String, output: String },
+    /// Reading the answer from the terminal failed.
+    Io(io::Error),
+    /// The dialog was closed through its [`DialogHandle`] or by dropping
+    /// its [`DialogFuture`].
+    Closed,
+    /// The dialog was left unanswered for longer than its timeout.
+    TimedOut,
+    /// The backend cannot show this kind of dialog.
+    Unsupported(String),
+}
+
+impl fmt::Display for DialogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DialogError::NoBackend => write!(f, "no dialog backend available"),
+            DialogError::UnknownBackend(name) => write!(f, "unknown dialog backend {:?}", name),
+            DialogError::NotInstalled(program) => write!(f, "{} is not installed", program),
+            DialogError::Spawn { program, source } => {
+                write!(f, "failed to start {}: {}", program, source)
+            }
+            DialogError::NoDisplay => write!(f, "no graphical display available"),
+            DialogError::Failed {
+                program,
+                code,
+                message,
+            } => {
+                match code {
+                    Some(code) => write!(f, "{} failed with exit code {}", program, code)?,
+                    None => write!(f, "{} failed", program)?,
+                }
+                if !message.is_empty() {
+                    write!(f, ": {}", message)?;
+                }
+                Ok(())
+            }
+            DialogError::InvalidOutput { program, output } => {
+                write!(f, "unexpected output from {}: {:?}", program, output)
+            }
+            DialogError::Io(err) => write!(f, "terminal I/O failed: {}", err),
+            DialogError::Closed => write!(f, "dialog was closed"),
+            DialogError::TimedOut => write!(f, "dialog timed out"),
+            DialogError::Unsupported(what) => {
+                write!(f, "not supported by the dialog backend: {}", what)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DialogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DialogError::Spawn { source, .. } => Some(source),
+            DialogError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A window that dialogs are stacked above and modal to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParentWindow {
+    /// An X11 window id, also used for XWayland windows.
+    X11(u64),
+    /// A Win32 `HWND`.
+    Win32(isize),
+}
+
+#[cfg(feature = "raw-window-handle")]
+impl ParentWindow {
+    /// Converts a window handle, `None` for windowing systems that dialogs
+    /// cannot be attached to.
+    pub fn from_raw(handle: raw_window_handle::RawWindowHandle) -> Option<Self> {
+        use raw_window_handle::RawWindowHandle;
+
+        match handle {
+            // c_ulong is only 32 bits wide on Windows
+            #[allow(clippy::useless_conversion)]
+            RawWindowHandle::Xlib(handle) => Some(ParentWindow::X11(u64::from(handle.window))),
+            RawWindowHandle::Xcb(handle) => Some(ParentWindow::X11(u64::from(handle.window.get()))),
+            RawWindowHandle::Win32(handle) => Some(ParentWindow::Win32(handle.hwnd.get())),
+            _ => None,
+        }
+    }
+}
+
 // Base dialog struct
+#[derive(Clone)]
 pub struct Dialog {
     title: String,
     message: String,
+    parent: Option<ParentWindow>,
+    timeout: Option<Duration>,
 }
 
 impl Dialog {
@@ -72,6 +202,8 @@ impl Dialog {
         Self {
             title: title.into(),
             message: message.into(),
+            parent: None,
+            timeout: None,
         }
     }
 
@@ -83,6 +215,14 @@ impl Dialog {
         &self.message
     }
 
+    pub fn parent(&self) -> Option<ParentWindow> {
+        self.parent
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
         self.title = title.into();
         self
@@ -93,29 +233,39 @@ impl Dialog {
         self
     }
 
-    /// Sanitize input for shell execution
-    fn sanitize_input(input: &str) -> String {
-        input
-            .replace("\"", "\\\"")
-            .replace("'", "\\'")
-            .replace("`", "\\`")
+    pub fn with_parent(mut self, parent: ParentWindow) -> Self {
+        self.parent = Some(parent);
+        self
     }
 
-    /// Verify path exists
-    fn verify_path(path: &str) -> Option<PathBuf> {
-        let path = Path::new(path);
-        if path.exists() {
-            Some(path.to_path_buf())
-        } else {
-            None
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_parent_window<W: raw_window_handle::HasWindowHandle + ?Sized>(
+        mut self,
+        window: &W,
+    ) -> Self {
+        if let Ok(handle) = window.window_handle() {
+            self.parent = ParentWindow::from_raw(handle.as_raw());
         }
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // Runs `show` under the watchdog if a timeout is set
+    fn show<T>(&self, show: impl FnOnce() -> Result<T, DialogError>) -> Result<T, DialogError> {
+        handle::with_timeout(self.timeout, show)
     }
 }
 
 // Message Box
+#[derive(Clone)]
 pub struct MessageBox {
     dialog: Dialog,
     icon: MessageBoxIcon,
+    buttons: Vec<String>,
 }
 
 impl MessageBox {
@@ -123,6 +273,7 @@ impl MessageBox {
         Self {
             dialog: Dialog::new(title, message),
             icon: MessageBoxIcon::Info,
+            buttons: Vec::new(),
         }
     }
 
@@ -131,65 +282,188 @@ impl MessageBox {
         self
     }
 
+    /// Button labels for [`run_modal_buttons`](Self::run_modal_buttons),
+    /// from left to right.
+    pub fn with_buttons(mut self, labels: &[&str]) -> Self {
+        self.buttons = labels.iter().map(|&s| s.to_string()).collect();
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        self.dialog.title()
+    }
+
+    pub fn message(&self) -> &str {
+        self.dialog.message()
+    }
+
     pub fn icon(&self) -> MessageBoxIcon {
         self.icon
     }
 
-    pub fn run_modal(&self) {
-        #[cfg(target_os = "macos")]
-        macos::message_box_ok(self);
+    /// Keeps the dialog above `parent` and modal to it, where the backend
+    /// supports that.
+    pub fn with_parent(mut self, parent: ParentWindow) -> Self {
+        self.dialog = self.dialog.with_parent(parent);
+        self
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        unix::message_box_ok(self);
+    /// Same as [`with_parent`](Self::with_parent) for any window that
+    /// exposes a raw handle. Windowing systems without a supported handle
+    /// leave the dialog unattached.
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_parent_window<W: raw_window_handle::HasWindowHandle + ?Sized>(
+        mut self,
+        window: &W,
+    ) -> Self {
+        self.dialog = self.dialog.with_parent_window(window);
+        self
+    }
 
-        #[cfg(target_os = "windows")]
-        windows::message_box_ok(self);
+    pub fn parent(&self) -> Option<ParentWindow> {
+        self.dialog.parent()
     }
 
-    pub fn run_modal_ok_cancel(&self, default: OkCancel) -> OkCancel {
-        #[cfg(target_os = "macos")]
-        return macos::message_box_ok_cancel(self, default);
+    /// Dismisses the dialog if it is left unanswered for `timeout`, the
+    /// `try_*` methods then return [`DialogError::TimedOut`].
+    ///
+    /// zenity and dialog time out by themselves, other dialog programs are
+    /// killed and native dialogs are sent a close request, give or take a
+    /// second. Backends reading from the terminal cannot be interrupted.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.dialog = self.dialog.with_timeout(timeout);
+        self
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::message_box_ok_cancel(self, default);
+    pub fn timeout(&self) -> Option<Duration> {
+        self.dialog.timeout()
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::message_box_ok_cancel(self, default);
+    pub fn buttons(&self) -> &[String] {
+        &self.buttons
+    }
 
-        #[allow(unreachable_code)]
-        OkCancel::Cancel
+    pub fn run_modal(&self) {
+        let _ = self.try_run_modal();
+    }
+
+    pub fn run_modal_ok_cancel(&self, default: OkCancel) -> OkCancel {
+        self.try_run_modal_ok_cancel(default)
+            .unwrap_or(OkCancel::Cancel)
     }
 
     pub fn run_modal_yes_no(&self, default: YesNo) -> YesNo {
-        #[cfg(target_os = "macos")]
-        return macos::message_box_yes_no(self, default);
+        self.try_run_modal_yes_no(default).unwrap_or(YesNo::No)
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::message_box_yes_no(self, default);
+    pub fn run_modal_yes_no_cancel(&self, default: YesNoCancel) -> YesNoCancel {
+        self.try_run_modal_yes_no_cancel(default)
+            .unwrap_or(YesNoCancel::Cancel)
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::message_box_yes_no(self, default);
+    /// Shows the buttons given to [`with_buttons`](Self::with_buttons) and
+    /// returns the index of the one pressed, `None` if the box was closed.
+    pub fn run_modal_buttons(&self) -> Option<usize> {
+        self.try_run_modal_buttons().ok().flatten()
+    }
 
-        #[allow(unreachable_code)]
-        YesNo::No
+    pub fn try_run_modal(&self) -> Result<(), DialogError> {
+        self.dialog.show(|| current_backend()?.message_box_ok(self))
     }
 
-    pub fn run_modal_yes_no_cancel(&self, default: YesNoCancel) -> YesNoCancel {
-        #[cfg(target_os = "macos")]
-        return macos::message_box_yes_no_cancel(self, default);
+    pub fn try_run_modal_ok_cancel(&self, default: OkCancel) -> Result<OkCancel, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.message_box_ok_cancel(self, default))
+    }
+
+    pub fn try_run_modal_yes_no(&self, default: YesNo) -> Result<YesNo, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.message_box_yes_no(self, default))
+    }
+
+    pub fn try_run_modal_yes_no_cancel(
+        &self,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.message_box_yes_no_cancel(self, default))
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::message_box_yes_no_cancel(self, default);
+    pub fn try_run_modal_buttons(&self) -> Result<Option<usize>, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.message_box_buttons(self))
+    }
+
+    /// Shows the message box without blocking the calling task.
+    pub fn run_async(&self) -> DialogFuture<()> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.message_box_ok(&this).await })
+    }
+
+    pub fn run_ok_cancel_async(&self, default: OkCancel) -> DialogFuture<OkCancel> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move {
+            backend.message_box_ok_cancel(&this, default).await
+        })
+    }
+
+    pub fn run_yes_no_async(&self, default: YesNo) -> DialogFuture<YesNo> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move {
+            backend.message_box_yes_no(&this, default).await
+        })
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::message_box_yes_no_cancel(self, default);
+    pub fn run_yes_no_cancel_async(&self, default: YesNoCancel) -> DialogFuture<YesNoCancel> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move {
+            backend.message_box_yes_no_cancel(&this, default).await
+        })
+    }
 
-        #[allow(unreachable_code)]
-        YesNoCancel::Cancel
+    pub fn run_buttons_async(&self) -> DialogFuture<Option<usize>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.message_box_buttons(&this).await })
+    }
+
+    /// Shows the message box on a background thread, see [`DialogHandle`].
+    pub fn spawn(&self) -> DialogHandle<()> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| this.dialog.show(|| backend.message_box_ok(&this)))
+    }
+
+    pub fn spawn_ok_cancel(&self, default: OkCancel) -> DialogHandle<OkCancel> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| {
+            this.dialog
+                .show(|| backend.message_box_ok_cancel(&this, default))
+        })
+    }
+
+    pub fn spawn_yes_no(&self, default: YesNo) -> DialogHandle<YesNo> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| {
+            this.dialog
+                .show(|| backend.message_box_yes_no(&this, default))
+        })
+    }
+
+    pub fn spawn_yes_no_cancel(&self, default: YesNoCancel) -> DialogHandle<YesNoCancel> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| {
+            this.dialog
+                .show(|| backend.message_box_yes_no_cancel(&this, default))
+        })
+    }
+
+    pub fn spawn_buttons(&self) -> DialogHandle<Option<usize>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| this.dialog.show(|| backend.message_box_buttons(&this)))
     }
 }
 
 // Input Box
+#[derive(Clone)]
 pub struct InputBox {
     dialog: Dialog,
     default_value: Option<String>,
@@ -215,6 +489,14 @@ impl InputBox {
         self
     }
 
+    pub fn title(&self) -> &str {
+        self.dialog.title()
+    }
+
+    pub fn message(&self) -> &str {
+        self.dialog.message()
+    }
+
     pub fn default_value(&self) -> Option<&str> {
         self.default_value.as_deref()
     }
@@ -223,49 +505,147 @@ impl InputBox {
         self.is_password
     }
 
+    pub fn with_parent(mut self, parent: ParentWindow) -> Self {
+        self.dialog = self.dialog.with_parent(parent);
+        self
+    }
+
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_parent_window<W: raw_window_handle::HasWindowHandle + ?Sized>(
+        mut self,
+        window: &W,
+    ) -> Self {
+        self.dialog = self.dialog.with_parent_window(window);
+        self
+    }
+
+    pub fn parent(&self) -> Option<ParentWindow> {
+        self.dialog.parent()
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.dialog = self.dialog.with_timeout(timeout);
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.dialog.timeout()
+    }
+
     pub fn run_modal(&self) -> Option<String> {
-        #[cfg(target_os = "macos")]
-        return macos::input_box(self);
+        self.try_run_modal().ok().flatten()
+    }
+
+    pub fn try_run_modal(&self) -> Result<Option<String>, DialogError> {
+        self.dialog.show(|| current_backend()?.input_box(self))
+    }
+
+    pub fn run_async(&self) -> DialogFuture<Option<String>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.input_box(&this).await })
+    }
+
+    pub fn spawn(&self) -> DialogHandle<Option<String>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| this.dialog.show(|| backend.input_box(&this)))
+    }
+}
+
+/// A named group of file name patterns, such as "Images" for `*.png *.jpg`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFilter {
+    description: String,
+    patterns: Vec<String>,
+}
+
+impl FileFilter {
+    pub fn new<S: Into<String>>(description: S, patterns: &[&str]) -> Self {
+        Self {
+            description: description.into(),
+            patterns: patterns.iter().map(|&s| s.to_string()).collect(),
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::input_box(self);
+    // Name to show for the group, dialogs need one even if none was given
+    fn label(&self) -> String {
+        if self.description.is_empty() {
+            self.patterns.join(" ")
+        } else {
+            self.description.clone()
+        }
+    }
+}
 
-        #[cfg(target_os = "windows")]
-        return windows::input_box(self);
+/// The file chosen in a save dialog and the filter group it was saved with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SaveResult {
+    pub path: PathBuf,
+    /// Index into [`FileDialog::filters`]. Dialogs that do not report the
+    /// selected group get the one matching the file name, or the initially
+    /// active one. `None` if the dialog has no filters or the user picked a
+    /// group the application did not add, such as "All Files" on Windows.
+    pub filter_index: Option<usize>,
+}
 
-        #[allow(unreachable_code)]
-        None
+impl From<PathBuf> for SaveResult {
+    fn from(path: PathBuf) -> Self {
+        Self {
+            path,
+            filter_index: None,
+        }
     }
 }
 
 // File Dialog
+#[derive(Clone)]
 pub struct FileDialog {
     dialog: Dialog,
-    path: String,
-    filter_patterns: Vec<String>,
-    filter_description: String,
+    path: PathBuf,
+    filters: Vec<FileFilter>,
+    default_filter: usize,
     multiple_selection: bool,
+    auto_extension: bool,
 }
 
 impl FileDialog {
     pub fn new<S: Into<String>>(title: S) -> Self {
         Self {
             dialog: Dialog::new(title, ""),
-            path: String::new(),
-            filter_patterns: Vec::new(),
-            filter_description: String::new(),
+            path: PathBuf::new(),
+            filters: Vec::new(),
+            default_filter: 0,
             multiple_selection: false,
+            auto_extension: false,
         }
     }
 
-    pub fn with_path<S: Into<String>>(mut self, path: S) -> Self {
-        self.path = path.into();
+    pub fn with_path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = path.as_ref().to_path_buf();
         self
     }
 
+    /// Adds a filter group, call it again to offer several.
     pub fn with_filter<S: Into<String>>(mut self, patterns: &[&str], description: S) -> Self {
-        self.filter_patterns = patterns.iter().map(|&s| s.to_string()).collect();
-        self.filter_description = description.into();
+        self.filters.push(FileFilter::new(description, patterns));
+        self
+    }
+
+    pub fn with_filters<I: IntoIterator<Item = FileFilter>>(mut self, filters: I) -> Self {
+        self.filters.extend(filters);
+        self
+    }
+
+    /// Index of the filter group that is active when the dialog opens.
+    pub fn with_default_filter(mut self, index: usize) -> Self {
+        self.default_filter = index;
         self
     }
 
@@ -274,74 +654,276 @@ impl FileDialog {
         self
     }
 
+    /// Appends the extension of the selected filter's first pattern when a
+    /// file name is saved without one.
+    pub fn with_auto_extension(mut self, auto_extension: bool) -> Self {
+        self.auto_extension = auto_extension;
+        self
+    }
+
+    pub fn title(&self) -> &str {
+        self.dialog.title()
+    }
+
+    /// The initial path as a string, empty if it is not valid UTF-8.
+    #[deprecated(note = "use `initial_path`, which also keeps non-UTF-8 paths")]
     pub fn path(&self) -> &str {
+        self.path.to_str().unwrap_or_default()
+    }
+
+    /// The file or directory the dialog starts at.
+    pub fn initial_path(&self) -> &Path {
         &self.path
     }
 
+    /// Patterns of the initially active filter group.
+    #[deprecated(note = "use `filters` and `default_filter`")]
     pub fn filter_patterns(&self) -> &[String] {
-        &self.filter_patterns
+        self.filters
+            .get(self.default_filter())
+            .map_or(&[], FileFilter::patterns)
     }
 
+    /// Description of the initially active filter group.
+    #[deprecated(note = "use `filters` and `default_filter`")]
     pub fn filter_description(&self) -> &str {
-        &self.filter_description
+        self.filters
+            .get(self.default_filter())
+            .map_or("", FileFilter::description)
+    }
+
+    pub fn filters(&self) -> &[FileFilter] {
+        &self.filters
+    }
+
+    /// Index of the initially active filter group, always within
+    /// [`filters`](Self::filters) unless there are none.
+    pub fn default_filter(&self) -> usize {
+        self.default_filter
+            .min(self.filters.len().saturating_sub(1))
+    }
+
+    fn save_with(&self, backend: &dyn DialogBackend) -> Result<Option<SaveResult>, DialogError> {
+        let saved = backend.save_file_dialog(self)?;
+        Ok(saved.map(|saved| self.complete_save(saved)))
+    }
+
+    // Fill in the filter group if the dialog could not tell, then add its
+    // extension if asked to
+    fn complete_save(&self, mut saved: SaveResult) -> SaveResult {
+        if saved.filter_index.is_none() && !self.filters.is_empty() {
+            let name = saved.path.file_name().unwrap_or_default().to_string_lossy();
+            let default = self.default_filter();
+            let matching = (default..self.filters.len()).chain(0..default).find(|&i| {
+                self.filters[i]
+                    .patterns()
+                    .iter()
+                    .any(|pattern| wildcard_match(pattern, &name))
+            });
+            saved.filter_index = Some(matching.unwrap_or(default));
+        }
+
+        if self.auto_extension && saved.path.extension().is_none() {
+            let extension = saved
+                .filter_index
+                .and_then(|i| self.filters.get(i))
+                .and_then(|filter| filter.patterns().first())
+                .and_then(|pattern| pattern.strip_prefix("*."))
+                .filter(|ext| !ext.is_empty() && !ext.contains(['*', '?']));
+            if let Some(extension) = extension {
+                saved.path.set_extension(extension);
+            }
+        }
+
+        saved
+    }
+
+    // Filter groups with the initially active one first, for dialogs that
+    // always start on their first group
+    fn filters_default_first(&self) -> impl Iterator<Item = &FileFilter> {
+        let default = self.default_filter();
+        let (before, after) = self.filters.split_at(default);
+        after.iter().chain(before)
     }
 
     pub fn multiple_selection(&self) -> bool {
         self.multiple_selection
     }
 
-    pub fn save_file(&self) -> Option<String> {
-        #[cfg(target_os = "macos")]
-        return macos::save_file_dialog(self);
+    pub fn auto_extension(&self) -> bool {
+        self.auto_extension
+    }
+
+    pub fn with_parent(mut self, parent: ParentWindow) -> Self {
+        self.dialog = self.dialog.with_parent(parent);
+        self
+    }
+
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_parent_window<W: raw_window_handle::HasWindowHandle + ?Sized>(
+        mut self,
+        window: &W,
+    ) -> Self {
+        self.dialog = self.dialog.with_parent_window(window);
+        self
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::save_file_dialog(self);
+    pub fn parent(&self) -> Option<ParentWindow> {
+        self.dialog.parent()
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::save_file_dialog(self);
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.dialog = self.dialog.with_timeout(timeout);
+        self
+    }
 
-        #[allow(unreachable_code)]
-        None
+    pub fn timeout(&self) -> Option<Duration> {
+        self.dialog.timeout()
+    }
+
+    pub fn save_file(&self) -> Option<String> {
+        self.try_save_file().ok().flatten()
     }
 
     pub fn open_file(&self) -> Option<String> {
-        self.open_files().and_then(|v| v.into_iter().next())
+        self.try_open_file().ok().flatten()
     }
 
     pub fn open_files(&self) -> Option<Vec<String>> {
-        #[cfg(target_os = "macos")]
-        return macos::open_file_dialog(self);
+        self.try_open_files().ok().flatten()
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::open_file_dialog(self);
+    pub fn select_folder(&self) -> Option<String> {
+        self.try_select_folder().ok().flatten()
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::open_file_dialog(self);
+    // The String results replace bytes that are not valid UTF-8, the
+    // `*_path` variants return file names as they are
+    pub fn try_save_file(&self) -> Result<Option<String>, DialogError> {
+        Ok(self.try_save_file_path()?.map(path_to_string))
+    }
 
-        #[allow(unreachable_code)]
-        None
+    pub fn save_file_result(&self) -> Option<SaveResult> {
+        self.try_save_file_result().ok().flatten()
     }
 
-    pub fn select_folder(&self) -> Option<String> {
-        #[cfg(target_os = "macos")]
-        return macos::select_folder_dialog(self);
+    pub fn try_save_file_result(&self) -> Result<Option<SaveResult>, DialogError> {
+        self.dialog
+            .show(|| self.save_with(current_backend()?.as_ref()))
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::select_folder_dialog(self);
+    pub fn try_open_file(&self) -> Result<Option<String>, DialogError> {
+        Ok(self.try_open_file_path()?.map(path_to_string))
+    }
+
+    pub fn try_open_files(&self) -> Result<Option<Vec<String>>, DialogError> {
+        let paths = self.try_open_file_paths()?;
+        Ok(paths.map(|paths| paths.into_iter().map(path_to_string).collect()))
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::select_folder_dialog(self);
+    pub fn try_select_folder(&self) -> Result<Option<String>, DialogError> {
+        Ok(self.try_select_folder_path()?.map(path_to_string))
+    }
 
-        #[allow(unreachable_code)]
-        None
+    pub fn save_file_path(&self) -> Option<PathBuf> {
+        self.try_save_file_path().ok().flatten()
+    }
+
+    pub fn open_file_path(&self) -> Option<PathBuf> {
+        self.try_open_file_path().ok().flatten()
+    }
+
+    pub fn open_file_paths(&self) -> Option<Vec<PathBuf>> {
+        self.try_open_file_paths().ok().flatten()
+    }
+
+    pub fn select_folder_path(&self) -> Option<PathBuf> {
+        self.try_select_folder_path().ok().flatten()
+    }
+
+    pub fn try_save_file_path(&self) -> Result<Option<PathBuf>, DialogError> {
+        Ok(self.try_save_file_result()?.map(|saved| saved.path))
+    }
+
+    pub fn try_open_file_path(&self) -> Result<Option<PathBuf>, DialogError> {
+        Ok(self
+            .try_open_file_paths()?
+            .and_then(|files| files.into_iter().next()))
+    }
+
+    pub fn try_open_file_paths(&self) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.open_file_dialog(self))
+    }
+
+    pub fn try_select_folder_path(&self) -> Result<Option<PathBuf>, DialogError> {
+        self.dialog
+            .show(|| current_backend()?.select_folder_dialog(self))
+    }
+
+    pub fn save_file_async(&self) -> DialogFuture<Option<PathBuf>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move {
+            let saved = backend.save_file_dialog(&this).await?;
+            Ok(saved.map(|saved| this.complete_save(saved).path))
+        })
+    }
+
+    pub fn open_file_async(&self) -> DialogFuture<Option<PathBuf>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move {
+            let files = backend.open_file_dialog(&this).await?;
+            Ok(files.and_then(|files| files.into_iter().next()))
+        })
+    }
+
+    pub fn open_files_async(&self) -> DialogFuture<Option<Vec<PathBuf>>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.open_file_dialog(&this).await })
+    }
+
+    pub fn select_folder_async(&self) -> DialogFuture<Option<PathBuf>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.select_folder_dialog(&this).await })
+    }
+
+    pub fn spawn_save_file(&self) -> DialogHandle<Option<PathBuf>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| {
+            this.dialog
+                .show(|| Ok(this.save_with(backend)?.map(|saved| saved.path)))
+        })
+    }
+
+    pub fn spawn_open_file(&self) -> DialogHandle<Option<PathBuf>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| {
+            this.dialog.show(|| {
+                Ok(backend
+                    .open_file_dialog(&this)?
+                    .and_then(|files| files.into_iter().next()))
+            })
+        })
+    }
+
+    pub fn spawn_open_files(&self) -> DialogHandle<Option<Vec<PathBuf>>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| this.dialog.show(|| backend.open_file_dialog(&this)))
+    }
+
+    pub fn spawn_select_folder(&self) -> DialogHandle<Option<PathBuf>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| this.dialog.show(|| backend.select_folder_dialog(&this)))
     }
 }
 
+#[derive(Clone)]
 pub enum DefaultColorValue {
     Hex(String),
     RGB([u8; 3]),
 }
 
+#[derive(Clone)]
 pub struct ColorChooser {
     dialog: Dialog,
     default_color: DefaultColorValue,
@@ -360,25 +942,52 @@ impl ColorChooser {
         self
     }
 
+    pub fn title(&self) -> &str {
+        self.dialog.title()
+    }
+
     pub fn default_color(&self) -> &DefaultColorValue {
         &self.default_color
     }
 
+    pub fn with_parent(mut self, parent: ParentWindow) -> Self {
+        self.dialog = self.dialog.with_parent(parent);
+        self
+    }
+
+    #[cfg(feature = "raw-window-handle")]
+    pub fn with_parent_window<W: raw_window_handle::HasWindowHandle + ?Sized>(
+        mut self,
+        window: &W,
+    ) -> Self {
+        self.dialog = self.dialog.with_parent_window(window);
+        self
+    }
+
+    pub fn parent(&self) -> Option<ParentWindow> {
+        self.dialog.parent()
+    }
+
     pub fn run_modal(&self) -> Option<(String, [u8; 3])> {
-        #[cfg(target_os = "macos")]
-        return macos::color_chooser_dialog(self);
+        self.try_run_modal().ok().flatten()
+    }
 
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::color_chooser_dialog(self);
+    pub fn try_run_modal(&self) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        current_backend()?.color_chooser_dialog(self)
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::color_chooser_dialog(self);
+    pub fn run_async(&self) -> DialogFuture<Option<(String, [u8; 3])>> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.color_chooser_dialog(&this).await })
+    }
 
-        #[allow(unreachable_code)]
-        None
+    pub fn spawn(&self) -> DialogHandle<Option<(String, [u8; 3])>> {
+        let this = self.clone();
+        DialogHandle::spawn(move |backend| backend.color_chooser_dialog(&this))
     }
 }
 
+#[derive(Clone)]
 pub struct Notification {
     title: String,
     message: String,
@@ -423,29 +1032,97 @@ impl Notification {
     }
 
     pub fn show(&self) -> bool {
-        #[cfg(target_os = "macos")]
-        return macos::notification(self);
-
-        #[cfg(all(unix, not(target_os = "macos")))]
-        return unix::notification(self);
+        self.try_show().is_ok()
+    }
 
-        #[cfg(target_os = "windows")]
-        return windows::notification(self);
+    pub fn try_show(&self) -> Result<(), DialogError> {
+        current_backend()?.notification(self)
+    }
 
-        #[allow(unreachable_code)]
-        false
+    pub fn show_async(&self) -> DialogFuture<()> {
+        let this = self.clone();
+        DialogFuture::spawn(move |backend| async move { backend.notification(&this).await })
     }
 }
 
 // Utility functions
+// Shell-style matching of `*` and `?`, case-insensitive like most file dialogs
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn path_to_string(path: PathBuf) -> String {
+    path.into_os_string()
+        .into_string()
+        .unwrap_or_else(|path| path.to_string_lossy().into_owned())
+}
+
+// "#rrggbb" or the "#rgb" shorthand. Anything else is black, the color
+// choosers start with by default.
 fn hex_to_rgb(hex: &str) -> [u8; 3] {
     let hex = hex.trim_start_matches('#');
-    let r = u8::from_str_radix(&hex[0..2], 16).unwrap_or(0);
-    let g = u8::from_str_radix(&hex[2..4], 16).unwrap_or(0);
-    let b = u8::from_str_radix(&hex[4..6], 16).unwrap_or(0);
-    [r, g, b]
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return [0, 0, 0];
+    }
+
+    let digits = |at: usize, len: usize| u8::from_str_radix(&hex[at..at + len], 16).unwrap_or(0);
+    match hex.len() {
+        6 => [digits(0, 2), digits(2, 2), digits(4, 2)],
+        3 => [digits(0, 1), digits(1, 1), digits(2, 1)].map(|digit| digit * 0x11),
+        _ => [0, 0, 0],
+    }
 }
 
 fn rgb_to_hex(rgb: &[u8; 3]) -> String {
     format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2])
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_colors() {
+        assert_eq!(hex_to_rgb("#ff8000"), [0xff, 0x80, 0x00]);
+        assert_eq!(hex_to_rgb("1a2B3c"), [0x1a, 0x2b, 0x3c]);
+        assert_eq!(hex_to_rgb("#fa0"), [0xff, 0xaa, 0x00]);
+        assert_eq!(rgb_to_hex(&hex_to_rgb("#123456")), "#123456");
+    }
+
+    #[test]
+    fn malformed_hex_colors_are_black() {
+        for hex in [
+            "",
+            "#",
+            "#ff",
+            "#ff800",
+            "#ff80001",
+            "#gg0000",
+            "#é12345",
+            "#ff\u{e9}",
+        ] {
+            assert_eq!(hex_to_rgb(hex), [0, 0, 0], "{:?}", hex);
+        }
+    }
+}
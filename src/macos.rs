@@ -1,49 +1,72 @@
 use super::*;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::Arc;
 
-// Helper function to run AppleScript and get the result
-fn run_osascript(script: &str) -> Option<String> {
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(script)
-        .output()
-        .ok()?;
+// AppleScript reports a dismissed dialog as error -128
+fn user_cancelled(output: &Output) -> bool {
+    String::from_utf8_lossy(&output.stderr).contains("(-128)")
+}
+
+// osascript is killed when the dialog is closed through its handle or
+// times out
+fn spawn_osascript(command: &mut Command) -> Result<Output, DialogError> {
+    let child = async_process::Command::new(command.get_program())
+        .args(command.get_args())
+        .kill_on_drop(true)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                DialogError::NotInstalled("osascript".to_string())
+            } else {
+                DialogError::Spawn {
+                    program: "osascript".to_string(),
+                    source: err,
+                }
+            }
+        })?;
+    handle::block_on(async { child.output().await.map_err(DialogError::Io) })
+}
 
+fn osascript_result(output: Output) -> Result<Option<String>, DialogError> {
     if output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
         let result = stdout.trim_end().to_string();
         if result.is_empty() {
-            None
+            Ok(None)
         } else {
-            Some(result)
+            Ok(Some(result))
         }
+    } else if user_cancelled(&output) {
+        Ok(None)
     } else {
-        None
+        Err(DialogError::Failed {
+            program: "osascript".to_string(),
+            code: output.status.code(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
     }
 }
 
+// Helper function to run AppleScript and get the result
+fn run_osascript(script: &str) -> Result<Option<String>, DialogError> {
+    let output = spawn_osascript(Command::new("osascript").arg("-e").arg(script))?;
+    osascript_result(output)
+}
+
 // Helper function to run multiple AppleScript commands
-fn run_osascript_multi(scripts: &[&str]) -> Option<String> {
+fn run_osascript_multi(scripts: &[&str]) -> Result<Option<String>, DialogError> {
     let mut command = Command::new("osascript");
 
     for script in scripts {
         command.arg("-e").arg(script);
     }
 
-    let output = command.output().ok()?;
-
-    if output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let result = stdout.trim_end().to_string();
-        if result.is_empty() {
-            None
-        } else {
-            Some(result)
-        }
-    } else {
-        None
-    }
+    let output = spawn_osascript(&mut command)?;
+    osascript_result(output)
 }
 
 // Convert path to POSIX format for AppleScript
@@ -59,7 +82,7 @@ fn to_posix_path(path: &str) -> String {
     if path.starts_with("alias ") {
         // Run osascript to convert alias to POSIX path
         let script = format!("get POSIX path of {}", path);
-        if let Some(posix_path) = run_osascript(&script) {
+        if let Ok(Some(posix_path)) = run_osascript(&script) {
             return posix_path;
         }
     } else if !path.starts_with('/') {
@@ -80,7 +103,7 @@ fn sanitize_for_applescript(s: &str) -> String {
 }
 
 // Message box implementation
-pub fn message_box_ok(msg_box: &MessageBox) {
+pub fn message_box_ok(msg_box: &MessageBox) -> Result<(), DialogError> {
     let title = sanitize_for_applescript(msg_box.dialog.title());
     let message = sanitize_for_applescript(msg_box.dialog.message());
 
@@ -96,10 +119,14 @@ pub fn message_box_ok(msg_box: &MessageBox) {
         message, title, icon_param
     );
 
-    let _ = run_osascript(&script);
+    run_osascript(&script)?;
+    Ok(())
 }
 
-pub fn message_box_ok_cancel(msg_box: &MessageBox, default: OkCancel) -> OkCancel {
+pub fn message_box_ok_cancel(
+    msg_box: &MessageBox,
+    default: OkCancel,
+) -> Result<OkCancel, DialogError> {
     let title = sanitize_for_applescript(msg_box.dialog.title());
     let message = sanitize_for_applescript(msg_box.dialog.message());
 
@@ -120,19 +147,19 @@ pub fn message_box_ok_cancel(msg_box: &MessageBox, default: OkCancel) -> OkCance
         message, title, default_button, icon_param
     );
 
-    match run_osascript(&script) {
+    match run_osascript(&script)? {
         Some(result) => {
             if result.contains("button returned:OK") {
-                OkCancel::Ok
+                Ok(OkCancel::Ok)
             } else {
-                OkCancel::Cancel
+                Ok(OkCancel::Cancel)
             }
         }
-        None => OkCancel::Cancel,
+        None => Ok(OkCancel::Cancel),
     }
 }
 
-pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> YesNo {
+pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> Result<YesNo, DialogError> {
     let title = sanitize_for_applescript(msg_box.dialog.title());
     let message = sanitize_for_applescript(msg_box.dialog.message());
 
@@ -153,19 +180,22 @@ pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> YesNo {
         message, title, default_button, icon_param
     );
 
-    match run_osascript(&script) {
+    match run_osascript(&script)? {
         Some(result) => {
             if result.contains("button returned:Yes") {
-                YesNo::Yes
+                Ok(YesNo::Yes)
             } else {
-                YesNo::No
+                Ok(YesNo::No)
             }
         }
-        None => YesNo::No,
+        None => Ok(YesNo::No),
     }
 }
 
-pub fn message_box_yes_no_cancel(msg_box: &MessageBox, default: YesNoCancel) -> YesNoCancel {
+pub fn message_box_yes_no_cancel(
+    msg_box: &MessageBox,
+    default: YesNoCancel,
+) -> Result<YesNoCancel, DialogError> {
     let title = sanitize_for_applescript(msg_box.dialog.title());
     let message = sanitize_for_applescript(msg_box.dialog.message());
 
@@ -187,21 +217,66 @@ pub fn message_box_yes_no_cancel(msg_box: &MessageBox, default: YesNoCancel) ->
         message, title, default_button, icon_param
     );
 
-    match run_osascript(&script) {
+    match run_osascript(&script)? {
         Some(result) => {
             if result.contains("button returned:Yes") {
-                YesNoCancel::Yes
+                Ok(YesNoCancel::Yes)
             } else if result.contains("button returned:No") {
-                YesNoCancel::No
+                Ok(YesNoCancel::No)
             } else {
-                YesNoCancel::Cancel
+                Ok(YesNoCancel::Cancel)
             }
         }
-        None => YesNoCancel::Cancel,
+        None => Ok(YesNoCancel::Cancel),
+    }
+}
+
+pub fn message_box_buttons(msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+    let title = sanitize_for_applescript(msg_box.dialog.title());
+    let message = sanitize_for_applescript(msg_box.dialog.message());
+    let buttons = msg_box.buttons();
+    if buttons.is_empty() {
+        return message_box_ok(msg_box).map(|()| Some(0));
+    }
+
+    let labels: Vec<String> = buttons
+        .iter()
+        .map(|label| format!("\"{}\"", sanitize_for_applescript(label)))
+        .collect();
+
+    let icon_param = match msg_box.icon() {
+        MessageBoxIcon::Info => "",
+        MessageBoxIcon::Warning => "with icon caution",
+        MessageBoxIcon::Error => "with icon stop",
+        MessageBoxIcon::Question => "with icon note",
+    };
+
+    // display dialog takes at most three buttons, more are offered as a list
+    let script = if buttons.len() <= 3 {
+        format!(
+            "button returned of (display dialog \"{}\" with title \"{}\" buttons {{{}}} {})",
+            message,
+            title,
+            labels.join(", "),
+            icon_param
+        )
+    } else {
+        format!(
+            "choose from list {{{}}} with prompt \"{}\" with title \"{}\"",
+            labels.join(", "),
+            message,
+            title
+        )
+    };
+
+    match run_osascript(&script)? {
+        Some(pressed) => Ok(buttons.iter().position(|label| *label == pressed)),
+        // AppleScript turns a button labelled "Cancel" into a cancellation
+        None => Ok(buttons.iter().position(|label| label == "Cancel")),
     }
 }
 
-pub fn input_box(input: &InputBox) -> Option<String> {
+pub fn input_box(input: &InputBox) -> Result<Option<String>, DialogError> {
     let title = sanitize_for_applescript(input.dialog.title());
     let message = sanitize_for_applescript(input.dialog.message());
     let default = input.default_value().unwrap_or("");
@@ -218,7 +293,7 @@ pub fn input_box(input: &InputBox) -> Option<String> {
         message, title, default, hidden_param
     );
 
-    match run_osascript(&script) {
+    let answer = match run_osascript(&script)? {
         Some(result) => {
             // Parse the result to extract text returned
             // Example: {button returned:OK, text returned:hello}
@@ -241,12 +316,14 @@ pub fn input_box(input: &InputBox) -> Option<String> {
             }
         }
         None => None,
-    }
+    };
+
+    Ok(answer)
 }
 
-pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
+pub fn save_file_dialog(dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
     let title = sanitize_for_applescript(dialog.dialog.title());
-    let path = to_posix_path(dialog.path());
+    let path = to_posix_path(&dialog.initial_path().to_string_lossy());
 
     // Prepare default location parameter if path exists
     let default_location = if !path.is_empty() {
@@ -279,10 +356,12 @@ pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
     };
 
     // Prepare filter if provided
-    let filter = if !dialog.filter_patterns().is_empty() {
+    // AppleScript has no filter groups, allow the types of all of them
+    let filter = if !dialog.filters().is_empty() {
         let patterns: Vec<String> = dialog
-            .filter_patterns()
+            .filters()
             .iter()
+            .flat_map(|filter| filter.patterns())
             .map(|p| {
                 // Extract extension from pattern (*.ext -> ext)
                 let ext = p.trim_start_matches("*.");
@@ -300,19 +379,20 @@ pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
         title, default_location, default_name, filter
     );
 
-    match run_osascript(&script) {
+    match run_osascript(&script)? {
         Some(alias_path) => {
             // Convert the returned alias to a POSIX path
             let conversion_script = format!("POSIX path of {}", alias_path);
-            run_osascript(&conversion_script)
+            let path = run_osascript(&conversion_script)?;
+            Ok(path.map(|path| PathBuf::from(path).into()))
         }
-        None => None,
+        None => Ok(None),
     }
 }
 
-pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
+pub fn open_file_dialog(dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
     let title = sanitize_for_applescript(dialog.dialog.title());
-    let path = to_posix_path(dialog.path());
+    let path = to_posix_path(&dialog.initial_path().to_string_lossy());
 
     // Prepare default location parameter if path exists
     let default_location = if !path.is_empty() {
@@ -329,10 +409,12 @@ pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
     };
 
     // Prepare filter if provided
-    let filter = if !dialog.filter_patterns().is_empty() {
+    // AppleScript has no filter groups, allow the types of all of them
+    let filter = if !dialog.filters().is_empty() {
         let patterns: Vec<String> = dialog
-            .filter_patterns()
+            .filters()
             .iter()
+            .flat_map(|filter| filter.patterns())
             .map(|p| {
                 // Extract extension from pattern (*.ext -> ext)
                 let ext = p.trim_start_matches("*.");
@@ -368,19 +450,19 @@ pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
     posixPaths as text
     "#;
 
-    match run_osascript_multi(&[&choose_script, prepare_result_script]) {
+    match run_osascript_multi(&[&choose_script, prepare_result_script])? {
         Some(result) => {
             // Split the paths that are joined by the delimiter
-            let paths: Vec<String> = result.split("||").map(|s| s.to_string()).collect();
-            Some(paths)
+            let paths: Vec<PathBuf> = result.split("||").map(PathBuf::from).collect();
+            Ok(Some(paths))
         }
-        None => None,
+        None => Ok(None),
     }
 }
 
-pub fn select_folder_dialog(dialog: &FileDialog) -> Option<String> {
+pub fn select_folder_dialog(dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
     let title = sanitize_for_applescript(dialog.dialog.title());
-    let path = to_posix_path(dialog.path());
+    let path = to_posix_path(&dialog.initial_path().to_string_lossy());
 
     // Prepare default location parameter if path exists
     let default_location = if !path.is_empty() {
@@ -394,17 +476,19 @@ pub fn select_folder_dialog(dialog: &FileDialog) -> Option<String> {
         title, default_location
     );
 
-    match run_osascript(&script) {
+    match run_osascript(&script)? {
         Some(alias_path) => {
             // Convert the returned alias to a POSIX path
             let conversion_script = format!("POSIX path of {}", alias_path);
-            run_osascript(&conversion_script)
+            Ok(run_osascript(&conversion_script)?.map(PathBuf::from))
         }
-        None => None,
+        None => Ok(None),
     }
 }
 
-pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])> {
+pub fn color_chooser_dialog(
+    chooser: &ColorChooser,
+) -> Result<Option<(String, [u8; 3])>, DialogError> {
     let title = sanitize_for_applescript(chooser.dialog.title());
 
     let default_rgb = match chooser.default_color() {
@@ -422,7 +506,10 @@ pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])>
         r, g, b
     );
 
-    let result = run_osascript(&script)?;
+    let result = match run_osascript(&script)? {
+        Some(result) => result,
+        None => return Ok(None),
+    };
 
     // Parse AppleScript color output format (e.g. "31, ,, 54, ,, 81")
     let rgb_values: Vec<u8> = result
@@ -438,16 +525,19 @@ pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])>
         .collect();
 
     if rgb_values.len() < 3 {
-        return None;
+        return Err(DialogError::InvalidOutput {
+            program: "osascript".to_string(),
+            output: result,
+        });
     }
 
     let rgb = [rgb_values[0], rgb_values[1], rgb_values[2]];
     let hex = super::rgb_to_hex(&rgb);
 
-    Some((hex, rgb))
+    Ok(Some((hex, rgb)))
 }
 
-pub fn notification(notification: &Notification) -> bool {
+pub fn notification(notification: &Notification) -> Result<(), DialogError> {
     let title = sanitize_for_applescript(notification.title());
     let message = sanitize_for_applescript(notification.message());
 
@@ -468,5 +558,87 @@ pub fn notification(notification: &Notification) -> bool {
         message, title, subtitle, sound
     );
 
-    run_osascript(&script).is_some()
+    run_osascript(&script)?;
+    Ok(())
+}
+
+// All dialogs are AppleScript run through osascript
+pub struct Osascript;
+
+impl DialogBackend for Osascript {
+    fn name(&self) -> &str {
+        "osascript"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notification_actions: false,
+            timeout: false,
+            parent_window: false,
+            ..Capabilities::all()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        message_box_ok(msg_box)
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        message_box_ok_cancel(msg_box, default)
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        message_box_yes_no(msg_box, default)
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        message_box_yes_no_cancel(msg_box, default)
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        message_box_buttons(msg_box)
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        input_box(input)
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        save_file_dialog(dialog)
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        open_file_dialog(dialog)
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        select_folder_dialog(dialog)
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        color_chooser_dialog(chooser)
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        self::notification(notification)
+    }
+}
+
+pub fn builtin_backends() -> Vec<Arc<dyn DialogBackend>> {
+    vec![Arc::new(Osascript)]
 }
@@ -0,0 +1,479 @@
+//! Helpers for testing code that shows dialogs.
+//!
+//! Install a [`MockBackend`] in a test, queue the answers the "user" gives
+//! with [`MockBackend::push_response`], run the code under test and inspect
+//! what it asked with [`MockBackend::requests`]. Nothing is displayed and
+//! nothing is read from stdin.
+
+use super::*;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A scripted answer to the next dialog shown through a [`MockBackend`].
+#[derive(Debug)]
+pub enum MockResponse {
+    /// Presses Ok in an Ok/Cancel box.
+    Ok,
+    /// Presses Yes in a Yes/No or Yes/No/Cancel box.
+    Yes,
+    /// Presses No in a Yes/No or Yes/No/Cancel box.
+    No,
+    /// Cancels or closes any dialog.
+    Cancel,
+    /// Enters text into an input box.
+    Input(String),
+    /// Picks one file or folder.
+    Path(PathBuf),
+    /// Picks several files in an open dialog.
+    Paths(Vec<PathBuf>),
+    /// Saves to a file with the filter group at this index selected.
+    SaveAs(PathBuf, usize),
+    /// Picks a color.
+    Color([u8; 3]),
+    /// Presses the custom button at this index.
+    Button(usize),
+    /// Makes the dialog fail instead of answering.
+    Error(DialogError),
+}
+
+/// The kind of dialog that was requested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogKind {
+    MessageBox,
+    OkCancel,
+    YesNo,
+    YesNoCancel,
+    Buttons,
+    Input,
+    SaveFile,
+    OpenFile,
+    SelectFolder,
+    Color,
+    Notification,
+}
+
+/// Everything a dialog would have shown to the user.
+///
+/// Fields that do not apply to a dialog kind are left empty.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct DialogRequest {
+    pub kind: DialogKind,
+    pub title: String,
+    pub message: String,
+    pub icon: Option<MessageBoxIcon>,
+    pub parent: Option<ParentWindow>,
+    /// Custom button labels.
+    pub buttons: Vec<String>,
+    /// Default button, default input text, initial path or default color,
+    /// formatted as text.
+    pub default: Option<String>,
+    pub password: bool,
+    pub filters: Vec<FileFilter>,
+    /// Index of the initially active filter group.
+    pub default_filter: usize,
+    pub multiple_selection: bool,
+    pub timeout: Option<Duration>,
+}
+
+impl DialogRequest {
+    fn new(kind: DialogKind, title: &str, message: &str) -> Self {
+        Self {
+            kind,
+            title: title.to_string(),
+            message: message.to_string(),
+            icon: None,
+            parent: None,
+            buttons: Vec::new(),
+            default: None,
+            password: false,
+            filters: Vec::new(),
+            default_filter: 0,
+            multiple_selection: false,
+            timeout: None,
+        }
+    }
+
+    fn message_box(kind: DialogKind, msg_box: &MessageBox) -> Self {
+        Self {
+            icon: Some(msg_box.icon()),
+            parent: msg_box.parent(),
+            timeout: msg_box.timeout(),
+            ..Self::new(kind, msg_box.title(), msg_box.message())
+        }
+    }
+
+    fn file(kind: DialogKind, dialog: &FileDialog) -> Self {
+        Self {
+            default: Some(dialog.initial_path().to_string_lossy().into_owned())
+                .filter(|p| !p.is_empty()),
+            filters: dialog.filters().to_vec(),
+            default_filter: dialog.default_filter(),
+            multiple_selection: dialog.multiple_selection(),
+            parent: dialog.parent(),
+            timeout: dialog.timeout(),
+            ..Self::new(kind, dialog.title(), "")
+        }
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    responses: VecDeque<MockResponse>,
+    requests: Vec<DialogRequest>,
+    capabilities: Option<Capabilities>,
+}
+
+/// A backend that answers dialogs from a queue of [`MockResponse`]s and
+/// records every [`DialogRequest`].
+///
+/// Clones share the same queue and log, so a clone can be installed while
+/// the test keeps the original to inspect. Message boxes without a choice
+/// and notifications only consume a queued response if it is an
+/// [`MockResponse::Error`]. Any other dialog panics when the queue is empty
+/// or the next response does not fit it.
+#[derive(Clone, Default)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// Keeps a [`MockBackend`] installed on the current thread until dropped.
+#[must_use = "the mock backend is uninstalled when the guard is dropped"]
+pub struct MockGuard {
+    previous: Option<Arc<dyn DialogBackend>>,
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        backend::set_thread_backend(self.previous.take());
+    }
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the answer to the next dialog.
+    pub fn push_response(&self, response: MockResponse) -> &Self {
+        self.state().responses.push_back(response);
+        self
+    }
+
+    pub fn with_response(self, response: MockResponse) -> Self {
+        self.push_response(response);
+        self
+    }
+
+    /// Reports these capabilities instead of supporting everything, to test
+    /// code that works around a missing feature.
+    pub fn with_capabilities(self, capabilities: Capabilities) -> Self {
+        self.state().capabilities = Some(capabilities);
+        self
+    }
+
+    /// Uses this backend for all dialogs shown on the current thread,
+    /// ahead of any backend selected with [`set_backend`].
+    pub fn install(&self) -> MockGuard {
+        MockGuard {
+            previous: backend::set_thread_backend(Some(Arc::new(self.clone()))),
+        }
+    }
+
+    /// All dialogs shown so far, oldest first.
+    pub fn requests(&self) -> Vec<DialogRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Number of queued responses that have not been used yet.
+    pub fn remaining_responses(&self) -> usize {
+        self.state().responses.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Records the request and hands out the next response
+    fn answer(&self, request: DialogRequest) -> MockResponse {
+        let mut state = self.state();
+        let kind = request.kind;
+        state.requests.push(request);
+        match state.responses.pop_front() {
+            Some(response) => response,
+            None => panic!("MockBackend: no response queued for {:?} dialog", kind),
+        }
+    }
+
+    // Records a request that needs no answer, failing only if told to
+    fn acknowledge(&self, request: DialogRequest) -> Result<(), DialogError> {
+        let mut state = self.state();
+        state.requests.push(request);
+        if let Some(MockResponse::Error(_)) = state.responses.front() {
+            if let Some(MockResponse::Error(err)) = state.responses.pop_front() {
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn unexpected(kind: DialogKind, response: MockResponse) -> ! {
+    panic!(
+        "MockBackend: response {:?} does not fit a {:?} dialog",
+        response, kind
+    )
+}
+
+impl DialogBackend for MockBackend {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.state().capabilities.unwrap_or_else(Capabilities::all)
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        self.acknowledge(DialogRequest::message_box(DialogKind::MessageBox, msg_box))
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        let request = DialogRequest {
+            default: Some(format!("{:?}", default)),
+            ..DialogRequest::message_box(DialogKind::OkCancel, msg_box)
+        };
+
+        match self.answer(request) {
+            MockResponse::Ok => Ok(OkCancel::Ok),
+            MockResponse::Cancel => Ok(OkCancel::Cancel),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::OkCancel, other),
+        }
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        let request = DialogRequest {
+            default: Some(format!("{:?}", default)),
+            ..DialogRequest::message_box(DialogKind::YesNo, msg_box)
+        };
+
+        match self.answer(request) {
+            MockResponse::Yes => Ok(YesNo::Yes),
+            MockResponse::No | MockResponse::Cancel => Ok(YesNo::No),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::YesNo, other),
+        }
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        let request = DialogRequest {
+            default: Some(format!("{:?}", default)),
+            ..DialogRequest::message_box(DialogKind::YesNoCancel, msg_box)
+        };
+
+        match self.answer(request) {
+            MockResponse::Yes => Ok(YesNoCancel::Yes),
+            MockResponse::No => Ok(YesNoCancel::No),
+            MockResponse::Cancel => Ok(YesNoCancel::Cancel),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::YesNoCancel, other),
+        }
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        let request = DialogRequest {
+            buttons: msg_box.buttons().to_vec(),
+            ..DialogRequest::message_box(DialogKind::Buttons, msg_box)
+        };
+
+        match self.answer(request) {
+            MockResponse::Button(index) => Ok(Some(index)),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::Buttons, other),
+        }
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        let request = DialogRequest {
+            default: input.default_value().map(|s| s.to_string()),
+            password: input.is_password(),
+            parent: input.parent(),
+            timeout: input.timeout(),
+            ..DialogRequest::new(DialogKind::Input, input.title(), input.message())
+        };
+
+        match self.answer(request) {
+            MockResponse::Input(text) => Ok(Some(text)),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::Input, other),
+        }
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        match self.answer(DialogRequest::file(DialogKind::SaveFile, dialog)) {
+            MockResponse::Path(path) => Ok(Some(path.into())),
+            MockResponse::SaveAs(path, filter_index) => Ok(Some(SaveResult {
+                path,
+                filter_index: Some(filter_index),
+            })),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::SaveFile, other),
+        }
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        match self.answer(DialogRequest::file(DialogKind::OpenFile, dialog)) {
+            MockResponse::Path(path) => Ok(Some(vec![path])),
+            MockResponse::Paths(paths) => Ok(Some(paths)),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::OpenFile, other),
+        }
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        match self.answer(DialogRequest::file(DialogKind::SelectFolder, dialog)) {
+            MockResponse::Path(path) => Ok(Some(path)),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::SelectFolder, other),
+        }
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        let default = match chooser.default_color() {
+            DefaultColorValue::Hex(hex) => hex.clone(),
+            DefaultColorValue::RGB(rgb) => rgb_to_hex(rgb),
+        };
+        let request = DialogRequest {
+            default: Some(default),
+            parent: chooser.parent(),
+            ..DialogRequest::new(DialogKind::Color, chooser.title(), "")
+        };
+
+        match self.answer(request) {
+            MockResponse::Color(rgb) => Ok(Some((rgb_to_hex(&rgb), rgb))),
+            MockResponse::Cancel => Ok(None),
+            MockResponse::Error(err) => Err(err),
+            other => unexpected(DialogKind::Color, other),
+        }
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        self.acknowledge(DialogRequest::new(
+            DialogKind::Notification,
+            notification.title(),
+            notification.message(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_in_order() {
+        let mock = MockBackend::new()
+            .with_response(MockResponse::Yes)
+            .with_response(MockResponse::Input("typed".to_string()))
+            .with_response(MockResponse::SaveAs(PathBuf::from("/tmp/out.png"), 1));
+        let _guard = mock.install();
+
+        let question = MessageBox::new("Question", "Really?");
+        assert_eq!(
+            question.try_run_modal_yes_no(YesNo::No).unwrap(),
+            YesNo::Yes
+        );
+        let input = InputBox::new("Name", "Your name");
+        assert_eq!(input.try_run_modal().unwrap().as_deref(), Some("typed"));
+        let saved = FileDialog::new("Save").try_save_file_result().unwrap();
+        assert_eq!(
+            saved,
+            Some(SaveResult {
+                path: PathBuf::from("/tmp/out.png"),
+                filter_index: Some(1),
+            })
+        );
+        assert_eq!(mock.remaining_responses(), 0);
+    }
+
+    #[test]
+    fn requests_are_recorded() {
+        let mock = MockBackend::new()
+            .with_response(MockResponse::Cancel)
+            .with_response(MockResponse::Error(DialogError::NoDisplay));
+        let _guard = mock.install();
+
+        MessageBox::new("Saved", "All done")
+            .with_icon(MessageBoxIcon::Warning)
+            .run_modal();
+        let dialog = FileDialog::new("Open")
+            .with_path("/tmp")
+            .with_filter(&["*.txt"], "Text")
+            .with_multiple_selection(true);
+        assert_eq!(dialog.try_open_files().unwrap(), None);
+        assert!(matches!(
+            InputBox::new("Name", "Your name").try_run_modal(),
+            Err(DialogError::NoDisplay)
+        ));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].kind, DialogKind::MessageBox);
+        assert_eq!(requests[0].title, "Saved");
+        assert_eq!(requests[0].message, "All done");
+        assert_eq!(requests[0].icon, Some(MessageBoxIcon::Warning));
+        assert_eq!(requests[1].kind, DialogKind::OpenFile);
+        assert_eq!(requests[1].default.as_deref(), Some("/tmp"));
+        assert_eq!(requests[1].filters, [FileFilter::new("Text", &["*.txt"])]);
+        assert!(requests[1].multiple_selection);
+        assert_eq!(requests[2].kind, DialogKind::Input);
+    }
+
+    #[test]
+    fn guard_restores_previous_backend() {
+        let outer = MockBackend::new().with_response(MockResponse::Ok);
+        let inner = MockBackend::new().with_response(MockResponse::Cancel);
+
+        let outer_guard = outer.install();
+        {
+            let _inner_guard = inner.install();
+            let question = MessageBox::new("Question", "Go on?");
+            assert_eq!(
+                question.try_run_modal_ok_cancel(OkCancel::Ok).unwrap(),
+                OkCancel::Cancel
+            );
+        }
+        let question = MessageBox::new("Question", "Go on?");
+        assert_eq!(
+            question.try_run_modal_ok_cancel(OkCancel::Ok).unwrap(),
+            OkCancel::Ok
+        );
+        assert_eq!(inner.requests().len(), 1);
+        assert_eq!(outer.requests().len(), 1);
+
+        drop(outer_guard);
+        let installed = backend::set_thread_backend(None);
+        assert!(installed.is_none());
+    }
+}
@@ -0,0 +1,170 @@
+use super::*;
+
+// Plain stdin/stdout prompts, always available
+pub struct Console;
+
+impl DialogBackend for Console {
+    fn name(&self) -> &str {
+        "console"
+    }
+
+    fn uses_terminal(&self) -> bool {
+        true
+    }
+
+    // Answers are typed in, there is nothing to browse or pick from
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            input_box: true,
+            yes_no_cancel: true,
+            notifications: command_exists("notify-send"),
+            ..Capabilities::default()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        println!("{}: {}", msg_box.title(), msg_box.message());
+        Ok(())
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        println!("{}: {} (y/n)", msg_box.title(), msg_box.message());
+        let input = read_console_line("")?.unwrap_or_default();
+        Ok(if input.to_lowercase() == "y" {
+            OkCancel::Ok
+        } else {
+            OkCancel::Cancel
+        })
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        println!("{}: {} (y/n)", msg_box.title(), msg_box.message());
+        let input = read_console_line("")?.unwrap_or_default();
+        Ok(if input.to_lowercase() == "y" {
+            YesNo::Yes
+        } else {
+            YesNo::No
+        })
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        println!("{}: {} (y/n/c)", msg_box.title(), msg_box.message());
+        let input = read_console_line("")?.unwrap_or_default();
+        match input.to_lowercase().as_str() {
+            "y" => Ok(YesNoCancel::Yes),
+            "n" => Ok(YesNoCancel::No),
+            _ => Ok(YesNoCancel::Cancel),
+        }
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        println!("{}: {}", msg_box.title(), msg_box.message());
+        if msg_box.buttons().len() < 2 {
+            return Ok(Some(0));
+        }
+
+        for (i, label) in msg_box.buttons().iter().enumerate() {
+            println!("  {}) {}", i + 1, label);
+        }
+        let input = read_console_line("> ")?.unwrap_or_default();
+        Ok(input
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n >= 1 && n <= msg_box.buttons().len())
+            .map(|n| n - 1))
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        println!("{}: {}", input.title(), input.message());
+        read_console_line("> ")
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        let path = dialog.initial_path();
+        println!(
+            "{}: Save file (default: {})",
+            dialog.title(),
+            path.display()
+        );
+        match read_console_line("> ")? {
+            Some(input) if !input.is_empty() => Ok(Some(PathBuf::from(input).into())),
+            Some(_) if !path.as_os_str().is_empty() => Ok(Some(path.to_path_buf().into())),
+            _ => Ok(None),
+        }
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        println!("{}: Open file", dialog.title());
+        match read_console_line("> ")? {
+            Some(input) if !input.is_empty() => Ok(Some(vec![PathBuf::from(input)])),
+            _ => Ok(None),
+        }
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        let path = dialog.initial_path();
+        println!(
+            "{}: Select folder (default: {})",
+            dialog.title(),
+            path.display()
+        );
+        match read_console_line("> ")? {
+            Some(input) if !input.is_empty() => Ok(Some(PathBuf::from(input))),
+            Some(_) if !path.as_os_str().is_empty() => Ok(Some(path.to_path_buf())),
+            _ => Ok(None),
+        }
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        let default_rgb = default_rgb(chooser);
+        let default_hex = rgb_to_hex(&default_rgb);
+
+        println!(
+            "{}: Choose color (default: {})",
+            chooser.title(),
+            default_hex
+        );
+        match read_console_line("> ")? {
+            Some(input) if input.is_empty() => Ok(Some((default_hex, default_rgb))),
+            Some(input) => color_answer("console", Some(input)),
+            None => Ok(None),
+        }
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        handle::block_on(notify(notification))
+    }
+}
+
+// notify-send when installed, otherwise the notification is printed. Also
+// used by the terminal programs, which have no popups of their own.
+pub(super) async fn notify(notification: &Notification) -> Result<(), DialogError> {
+    if let Some(result) = notify_send(notification).await {
+        return result;
+    }
+
+    println!(
+        "Notification: {} - {}",
+        notification.title(),
+        notification.message()
+    );
+    if let Some(subtitle) = notification.subtitle() {
+        println!("  {}", subtitle);
+    }
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use super::*;
+
+// dialog(1), also known as cdialog. It draws on the terminal, so it runs
+// with our stdio and prints answers on stderr. There is no color picker, the
+// color is typed into an input box.
+pub struct Cdialog;
+
+impl Cdialog {
+    async fn yes_no(&self, msg_box: &MessageBox, default_no: bool) -> Result<bool, DialogError> {
+        let mut cmd = cdialog(&msg_box.dialog);
+        if default_no {
+            cmd.arg("--defaultno");
+        }
+        cmd.arg("--yesno").arg(msg_box.message()).arg("0").arg("0");
+        confirm_terminal(&mut cmd).await
+    }
+
+    // Up to three buttons as Yes, Extra and No, in that order on screen
+    async fn buttons(
+        &self,
+        msg_box: &MessageBox,
+        default: usize,
+    ) -> Result<Option<usize>, DialogError> {
+        let buttons = msg_box.buttons();
+        let mut cmd = cdialog(&msg_box.dialog);
+
+        match buttons {
+            [] | [_] => {
+                if let [ok] = buttons {
+                    cmd.arg("--ok-label").arg(ok);
+                }
+                cmd.arg("--msgbox");
+            }
+            [yes, no] => {
+                cmd.arg("--yes-label")
+                    .arg(yes)
+                    .arg("--no-label")
+                    .arg(no)
+                    .arg("--default-button")
+                    .arg(if default == 1 { "no" } else { "yes" })
+                    .arg("--yesno");
+            }
+            [yes, extra, no] => {
+                cmd.arg("--yes-label")
+                    .arg(yes)
+                    .arg("--extra-button")
+                    .arg("--extra-label")
+                    .arg(extra)
+                    .arg("--no-label")
+                    .arg(no)
+                    .arg("--default-button")
+                    .arg(match default {
+                        1 => "extra",
+                        2 => "no",
+                        _ => "yes",
+                    })
+                    .arg("--yesno");
+            }
+            _ => return menu(cdialog(&msg_box.dialog), msg_box).await,
+        }
+
+        cmd.arg(msg_box.message()).arg("0").arg("0");
+
+        let status = run_status(&mut cmd).await?;
+        match (status.code(), buttons.len()) {
+            (Some(0), _) => Ok(Some(0)),
+            (Some(1), 2) => Ok(Some(1)),
+            (Some(3), 3) => Ok(Some(1)),
+            (Some(1), 3) => Ok(Some(2)),
+            (Some(255), _) => Ok(None),
+            (code, _) => Err(failed(&cmd, code, &[])),
+        }
+    }
+}
+
+impl ProgramBackend for Cdialog {
+    fn name(&self) -> &str {
+        "dialog"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("dialog")
+    }
+
+    fn uses_terminal(&self) -> bool {
+        true
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("dialog").await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            timeout: true,
+            ..dialog_capabilities()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        confirm_terminal(
+            cdialog(&msg_box.dialog)
+                .arg("--msgbox")
+                .arg(msg_box.message())
+                .arg("0")
+                .arg("0"),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        Ok(
+            if self.yes_no(msg_box, default == OkCancel::Cancel).await? {
+                OkCancel::Ok
+            } else {
+                OkCancel::Cancel
+            },
+        )
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        Ok(if self.yes_no(msg_box, default == YesNo::No).await? {
+            YesNo::Yes
+        } else {
+            YesNo::No
+        })
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        let msg_box = msg_box.clone().with_buttons(&["Yes", "No", "Cancel"]);
+        let default = match default {
+            YesNoCancel::Yes => 0,
+            YesNoCancel::No => 1,
+            YesNoCancel::Cancel => 2,
+        };
+        Ok(match self.buttons(&msg_box, default).await? {
+            Some(0) => YesNoCancel::Yes,
+            Some(1) => YesNoCancel::No,
+            _ => YesNoCancel::Cancel,
+        })
+    }
+
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        self.buttons(msg_box, 0).await
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        input_box(cdialog(&input.dialog), input).await
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        let path = file_select(cdialog(&dialog.dialog), dialog, "--fselect").await?;
+        Ok(path.map(SaveResult::from))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        let path = file_select(cdialog(&dialog.dialog), dialog, "--fselect").await?;
+        Ok(path.map(|path| vec![path]))
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        file_select(cdialog(&dialog.dialog), dialog, "--dselect").await
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        color_input(cdialog(&chooser.dialog), chooser, "dialog").await
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        console::notify(notification).await
+    }
+}
+
+fn cdialog(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("dialog");
+    cmd.arg("--title").arg(dialog.title());
+    if let Some(timeout) = dialog.timeout() {
+        // dialog reports a timeout like Escape unless told otherwise
+        cmd.env("DIALOG_TIMEOUT", TIMEOUT_STATUS.to_string())
+            .arg("--timeout")
+            .arg(timeout_secs(timeout).to_string());
+    }
+    cmd
+}
+
+// Shared with Xdialog, which takes the same boxes. Everything but
+// multiple selection, file filters, a real color picker, timeouts and
+// parent windows.
+pub(super) fn dialog_capabilities() -> Capabilities {
+    Capabilities {
+        input_box: true,
+        file_dialogs: true,
+        yes_no_cancel: true,
+        password: true,
+        notifications: command_exists("notify-send"),
+        ..Capabilities::default()
+    }
+}
+
+pub(super) async fn input_box(
+    mut cmd: Command,
+    input: &InputBox,
+) -> Result<Option<String>, DialogError> {
+    cmd.arg(if input.is_password() {
+        "--passwordbox"
+    } else {
+        "--inputbox"
+    })
+    .arg(input.message())
+    .arg("0")
+    .arg("0");
+
+    if let Some(default) = input.default_value() {
+        cmd.arg(default);
+    }
+
+    let answer = read_stderr_answer(&mut cmd).await?;
+    Ok(answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+// `--fselect` or `--dselect`, which start in the directory of the initial
+// path. Saving types a new name into the same box.
+pub(super) async fn file_select(
+    mut cmd: Command,
+    dialog: &FileDialog,
+    kind: &str,
+) -> Result<Option<PathBuf>, DialogError> {
+    let path = if dialog.initial_path().as_os_str().is_empty() {
+        Path::new("./")
+    } else {
+        dialog.initial_path()
+    };
+    cmd.arg(kind).arg(path).arg("0").arg("60");
+
+    let answer = read_stderr_answer(&mut cmd).await?;
+    Ok(answer
+        .filter(|bytes| !bytes.is_empty())
+        .map(|bytes| PathBuf::from(OsStr::from_bytes(&bytes))))
+}
+
+// More buttons than fit in a box, picked from a menu instead
+pub(super) async fn menu(
+    mut cmd: Command,
+    msg_box: &MessageBox,
+) -> Result<Option<usize>, DialogError> {
+    let buttons = msg_box.buttons();
+    cmd.arg("--menu")
+        .arg(msg_box.message())
+        .arg("0")
+        .arg("0")
+        .arg(buttons.len().to_string());
+    for (i, label) in buttons.iter().enumerate() {
+        cmd.arg((i + 1).to_string()).arg(label);
+    }
+
+    let answer = read_stderr_answer(&mut cmd).await?;
+    Ok(answer
+        .and_then(|tag| String::from_utf8_lossy(&tag).trim().parse::<usize>().ok())
+        .filter(|&n| n >= 1 && n <= buttons.len())
+        .map(|n| n - 1))
+}
+
+pub(super) async fn color_input(
+    mut cmd: Command,
+    chooser: &ColorChooser,
+    program: &str,
+) -> Result<Option<(String, [u8; 3])>, DialogError> {
+    cmd.arg("--inputbox")
+        .arg("Color as #rrggbb:")
+        .arg("0")
+        .arg("0")
+        .arg(rgb_to_hex(&default_rgb(chooser)));
+
+    let answer = read_stderr_answer(&mut cmd).await?;
+    color_answer(
+        program,
+        answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+    )
+}
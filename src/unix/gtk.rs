@@ -0,0 +1,890 @@
+use super::*;
+use std::ffi::{c_char, c_int, c_uint, c_ulong, c_void, CStr, CString};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+// GTK 3 dialogs shown inside this process, which is quicker than starting
+// zenity and keeps them stacked on the application's windows. libgtk-3 is
+// opened at runtime like TaskDialogIndirect on Windows, so the feature
+// builds without GTK and the backend is unavailable where it is missing.
+pub struct Gtk;
+
+impl DialogBackend for Gtk {
+    fn name(&self) -> &str {
+        "gtk"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && library().is_ok()
+    }
+
+    fn version(&self) -> Option<String> {
+        let gtk = library().ok()?;
+        unsafe {
+            Some(format!(
+                "{}.{}.{}",
+                (gtk.get_major_version)(),
+                (gtk.get_minor_version)(),
+                (gtk.get_micro_version)()
+            ))
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notifications: command_exists("notify-send"),
+            notification_actions: false,
+            timeout: false,
+            ..Capabilities::all()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        message(msg_box, &[("_OK", RESPONSE_OK)], RESPONSE_OK)?;
+        Ok(())
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        let default = match default {
+            OkCancel::Ok => RESPONSE_OK,
+            OkCancel::Cancel => RESPONSE_CANCEL,
+        };
+        let buttons = [("_Cancel", RESPONSE_CANCEL), ("_OK", RESPONSE_OK)];
+        Ok(match message(msg_box, &buttons, default)? {
+            RESPONSE_OK => OkCancel::Ok,
+            _ => OkCancel::Cancel,
+        })
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        let default = match default {
+            YesNo::Yes => RESPONSE_YES,
+            YesNo::No => RESPONSE_NO,
+        };
+        let buttons = [("_No", RESPONSE_NO), ("_Yes", RESPONSE_YES)];
+        Ok(match message(msg_box, &buttons, default)? {
+            RESPONSE_YES => YesNo::Yes,
+            _ => YesNo::No,
+        })
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        let default = match default {
+            YesNoCancel::Yes => RESPONSE_YES,
+            YesNoCancel::No => RESPONSE_NO,
+            YesNoCancel::Cancel => RESPONSE_CANCEL,
+        };
+        let buttons = [
+            ("_Cancel", RESPONSE_CANCEL),
+            ("_No", RESPONSE_NO),
+            ("_Yes", RESPONSE_YES),
+        ];
+        Ok(match message(msg_box, &buttons, default)? {
+            RESPONSE_YES => YesNoCancel::Yes,
+            RESPONSE_NO => YesNoCancel::No,
+            _ => YesNoCancel::Cancel,
+        })
+    }
+
+    // Custom buttons answer with their index, GTK's own responses are
+    // negative
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        if msg_box.buttons().is_empty() {
+            return self.message_box_ok(msg_box).map(|()| Some(0));
+        }
+
+        // GTK takes underscores in button labels as mnemonics
+        let labels: Vec<String> = msg_box
+            .buttons()
+            .iter()
+            .map(|label| label.replace('_', "__"))
+            .collect();
+        let buttons: Vec<(&str, c_int)> = labels
+            .iter()
+            .zip(0..)
+            .map(|(label, i)| (label.as_str(), i))
+            .collect();
+
+        let response = message(msg_box, &buttons, 0)?;
+        Ok(usize::try_from(response).ok())
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        let input = input.clone();
+        show(move |gtk, shown| unsafe {
+            let widget =
+                gtk.message_dialog(&input.dialog, input.message(), MessageBoxIcon::Question);
+            gtk.add_button(widget, "_Cancel", RESPONSE_CANCEL);
+            gtk.add_button(widget, "_OK", RESPONSE_OK);
+            (gtk.dialog_set_default_response)(widget, RESPONSE_OK);
+
+            let entry = (gtk.entry_new)();
+            if let Some(default) = input.default_value() {
+                (gtk.entry_set_text)(entry, cstring(default).as_ptr());
+            }
+            (gtk.entry_set_visibility)(entry, c_int::from(!input.is_password()));
+            (gtk.entry_set_activates_default)(entry, 1);
+            (gtk.container_add)((gtk.message_dialog_get_message_area)(widget), entry);
+            (gtk.widget_show)(entry);
+
+            let response = shown.run(gtk, widget);
+            let text = (response == RESPONSE_OK).then(|| {
+                CStr::from_ptr((gtk.entry_get_text)(entry))
+                    .to_string_lossy()
+                    .into_owned()
+            });
+            (gtk.widget_destroy)(widget);
+            text
+        })
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        let dialog = dialog.clone();
+        show(move |gtk, shown| unsafe {
+            let (widget, filters) = gtk.file_chooser(&dialog, FILE_CHOOSER_SAVE, "_Save");
+            (gtk.file_chooser_set_do_overwrite_confirmation)(widget, 1);
+
+            let path = absolute(dialog.initial_path());
+            if path.is_dir() {
+                (gtk.file_chooser_set_current_folder)(widget, path_cstring(&path).as_ptr());
+            } else if let (Some(dir), Some(name)) = (path.parent(), path.file_name()) {
+                (gtk.file_chooser_set_current_folder)(widget, path_cstring(dir).as_ptr());
+                let name = cstring(&name.to_string_lossy());
+                (gtk.file_chooser_set_current_name)(widget, name.as_ptr());
+            }
+
+            let saved = (shown.run(gtk, widget) == RESPONSE_ACCEPT)
+                .then(|| gtk.filenames(widget).into_iter().next())
+                .flatten()
+                .map(|path| {
+                    let selected = (gtk.file_chooser_get_filter)(widget);
+                    SaveResult {
+                        path,
+                        filter_index: filters.iter().position(|&filter| filter == selected),
+                    }
+                });
+            (gtk.widget_destroy)(widget);
+            saved
+        })
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        let dialog = dialog.clone();
+        show(move |gtk, shown| unsafe {
+            let (widget, _) = gtk.file_chooser(&dialog, FILE_CHOOSER_OPEN, "_Open");
+            (gtk.file_chooser_set_select_multiple)(
+                widget,
+                c_int::from(dialog.multiple_selection()),
+            );
+            gtk.start_at(widget, dialog.initial_path());
+
+            let paths = (shown.run(gtk, widget) == RESPONSE_ACCEPT)
+                .then(|| gtk.filenames(widget))
+                .filter(|paths| !paths.is_empty());
+            (gtk.widget_destroy)(widget);
+            paths
+        })
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        let dialog = dialog.clone();
+        show(move |gtk, shown| unsafe {
+            let (widget, _) = gtk.file_chooser(&dialog, FILE_CHOOSER_SELECT_FOLDER, "_Select");
+            gtk.start_at(widget, dialog.initial_path());
+
+            let path = (shown.run(gtk, widget) == RESPONSE_ACCEPT)
+                .then(|| gtk.filenames(widget).into_iter().next())
+                .flatten();
+            (gtk.widget_destroy)(widget);
+            path
+        })
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        let chooser = chooser.clone();
+        show(move |gtk, shown| unsafe {
+            let title = cstring(chooser.title());
+            let widget =
+                (gtk.color_chooser_dialog_new)(title.as_ptr(), gtk.parent(chooser.parent()));
+            (gtk.window_set_modal)(widget, 1);
+            (gtk.color_chooser_set_use_alpha)(widget, 0);
+
+            let [red, green, blue] = default_rgb(&chooser).map(|c| f64::from(c) / 255.0);
+            let mut color = GdkRgba {
+                red,
+                green,
+                blue,
+                alpha: 1.0,
+            };
+            (gtk.color_chooser_set_rgba)(widget, &color);
+
+            let response = shown.run(gtk, widget);
+            (gtk.color_chooser_get_rgba)(widget, &mut color);
+            (gtk.widget_destroy)(widget);
+
+            (response == RESPONSE_OK).then(|| {
+                let rgb = [color.red, color.green, color.blue]
+                    .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                (rgb_to_hex(&rgb), rgb)
+            })
+        })
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = async_io::block_on(notify_send(notification)) {
+            return result;
+        }
+
+        let msg_box = MessageBox::new(notification.title(), notification.message());
+        self.message_box_ok(&msg_box)
+    }
+}
+
+// A message dialog with `buttons`, answering with the response of the one
+// pressed
+fn message(
+    msg_box: &MessageBox,
+    buttons: &[(&str, c_int)],
+    default: c_int,
+) -> Result<c_int, DialogError> {
+    let msg_box = msg_box.clone();
+    let buttons: Vec<(String, c_int)> = buttons
+        .iter()
+        .map(|&(label, response)| (label.to_string(), response))
+        .collect();
+
+    show(move |gtk, shown| unsafe {
+        let widget = gtk.message_dialog(&msg_box.dialog, msg_box.message(), msg_box.icon());
+        for (label, response) in &buttons {
+            gtk.add_button(widget, label, *response);
+        }
+        (gtk.dialog_set_default_response)(widget, default);
+
+        let response = shown.run(gtk, widget);
+        (gtk.widget_destroy)(widget);
+        response
+    })
+}
+
+// GTK may only be used on the thread iterating its main context. Dialogs
+// asked for from the application's GTK callbacks are shown right there,
+// other threads hand them over and wait. If nobody picks the dialog up, the
+// application loaded GTK without running its main loop, and the waiting
+// thread iterates the main context itself when nobody else owns it.
+fn show<T: Send + 'static>(
+    dialog: impl FnOnce(&'static Library, &Shown) -> T + Send + 'static,
+) -> Result<T, DialogError> {
+    let gtk = library()?;
+    let shown = Arc::new(Shown::default());
+
+    let _hook = handle::on_cancel({
+        let shown = shown.clone();
+        move || {
+            shown.close();
+            let shown = shown.clone();
+            invoke(gtk, move || shown.respond(gtk));
+        }
+    });
+
+    let owner = unsafe { (gtk.main_context_is_owner)((gtk.main_context_default)()) };
+    if owner != 0 {
+        return Ok(dialog(gtk, &shown));
+    }
+
+    let (sender, answer) = mpsc::channel();
+    invoke(gtk, {
+        let shown = shown.clone();
+        move || {
+            if !shown.start() {
+                return;
+            }
+            let result = panic::catch_unwind(AssertUnwindSafe(|| dialog(gtk, &shown)));
+            let _ = sender.send(result);
+        }
+    });
+
+    let answer = match answer.recv_timeout(PICKUP_TIMEOUT) {
+        Err(RecvTimeoutError::Timeout) => unsafe { takeover(gtk, &shown, &answer)? },
+        answer => answer.map_err(|_| mpsc::RecvError),
+    };
+    match answer {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(payload)) => panic::resume_unwind(payload),
+        Err(_) => Err(DialogError::Closed),
+    }
+}
+
+// How long a dialog handed over may wait before the main context counts as
+// not iterated
+const PICKUP_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Iterates GTK's main context on this thread until the answer comes in, the
+// dialog handed over runs in there. Fails if another thread owns the context
+// without iterating it, the dialog is then never shown.
+unsafe fn takeover<T>(
+    gtk: &Library,
+    shown: &Shown,
+    answer: &Receiver<T>,
+) -> Result<Result<T, mpsc::RecvError>, DialogError> {
+    let context = (gtk.main_context_default)();
+    if (gtk.main_context_acquire)(context) == 0 {
+        if shown.abandon() {
+            return Err(DialogError::Failed {
+                program: "gtk".to_string(),
+                code: None,
+                message: "GTK's main context is owned by a thread that does not iterate it"
+                    .to_string(),
+            });
+        }
+        // Picked up after all
+        return Ok(answer.recv());
+    }
+
+    let result = loop {
+        match answer.try_recv() {
+            Ok(value) => break Ok(value),
+            Err(TryRecvError::Empty) => {
+                (gtk.main_context_iteration)(context, 1);
+            }
+            Err(TryRecvError::Disconnected) => break Err(mpsc::RecvError),
+        }
+    };
+    (gtk.main_context_release)(context);
+    Ok(result)
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// Runs `job` on the thread iterating GTK's main context, right away if that
+// is this one
+fn invoke(gtk: &Library, job: impl FnOnce() + Send + 'static) {
+    let job: Box<Job> = Box::new(Box::new(job));
+    unsafe {
+        (gtk.main_context_invoke)(
+            (gtk.main_context_default)(),
+            run_job,
+            Box::into_raw(job).cast(),
+        );
+    }
+}
+
+unsafe extern "C" fn run_job(data: *mut c_void) -> c_int {
+    let job = Box::from_raw(data.cast::<Job>());
+    job();
+    G_SOURCE_REMOVE
+}
+
+// The dialog on screen, so that closing its handle can answer it
+#[derive(Default)]
+struct Shown {
+    state: Mutex<ShownState>,
+}
+
+#[derive(Default)]
+struct ShownState {
+    dialog: Option<Widget>,
+    closed: bool,
+    started: bool,
+    abandoned: bool,
+}
+
+struct Widget(Object);
+
+// Only touched on the GTK thread
+unsafe impl Send for Widget {}
+
+impl Shown {
+    fn lock(&self) -> MutexGuard<'_, ShownState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // gtk_dialog_run, unless the dialog was closed before it came up
+    fn run(&self, gtk: &Library, dialog: Object) -> c_int {
+        {
+            let mut state = self.lock();
+            if state.closed {
+                return RESPONSE_DELETE_EVENT;
+            }
+            state.dialog = Some(Widget(dialog));
+        }
+
+        let response = unsafe { (gtk.dialog_run)(dialog) };
+        self.lock().dialog = None;
+        response
+    }
+
+    fn close(&self) {
+        self.lock().closed = true;
+    }
+
+    // On the GTK thread, whether the dialog is still wanted
+    fn start(&self) -> bool {
+        let mut state = self.lock();
+        state.started = !state.abandoned;
+        state.started
+    }
+
+    // Gives up on a dialog that was handed over, unless it started already
+    fn abandon(&self) -> bool {
+        let mut state = self.lock();
+        state.abandoned = !state.started;
+        state.abandoned
+    }
+
+    // On the GTK thread, ends gtk_dialog_run like the window's close button
+    fn respond(&self, gtk: &Library) {
+        if let Some(Widget(dialog)) = self.lock().dialog {
+            unsafe { (gtk.dialog_response)(dialog, RESPONSE_DELETE_EVENT) };
+        }
+    }
+}
+
+enum State {
+    Missing,
+    NoDisplay,
+    Ready(Box<Library>),
+}
+
+fn library() -> Result<&'static Library, DialogError> {
+    static STATE: OnceLock<State> = OnceLock::new();
+    match STATE.get_or_init(start) {
+        State::Ready(gtk) => Ok(gtk),
+        State::Missing => Err(DialogError::NotInstalled(LIBRARY.to_string())),
+        State::NoDisplay => Err(DialogError::NoDisplay),
+    }
+}
+
+// Uses the application's GTK if it has opened a display already, otherwise
+// starts GTK on a thread of our own that keeps iterating its main context
+fn start() -> State {
+    let Some(gtk) = Library::open() else {
+        return State::Missing;
+    };
+    if unsafe { !(gtk.display_get_default)().is_null() } {
+        return State::Ready(Box::new(gtk));
+    }
+
+    let (init_check, main) = (gtk.init_check, gtk.main);
+    let (context_default, acquire) = (gtk.main_context_default, gtk.main_context_acquire);
+    let (sender, started) = mpsc::channel();
+    thread::spawn(move || unsafe {
+        // Owning the context before anyone hands over a dialog keeps
+        // g_main_context_invoke from running it on the caller's thread
+        let started =
+            init_check(ptr::null_mut(), ptr::null_mut()) != 0 && acquire(context_default()) != 0;
+        let _ = sender.send(started);
+        if started {
+            main();
+        }
+    });
+
+    match started.recv() {
+        Ok(true) => State::Ready(Box::new(gtk)),
+        _ => State::NoDisplay,
+    }
+}
+
+const LIBRARY: &str = "libgtk-3.so.0";
+
+type Object = *mut c_void;
+
+#[repr(C)]
+struct GList {
+    data: *mut c_void,
+    next: *mut GList,
+    prev: *mut GList,
+}
+
+#[repr(C)]
+struct GSList {
+    data: *mut c_void,
+    next: *mut GSList,
+}
+
+#[repr(C)]
+struct GdkRgba {
+    red: f64,
+    green: f64,
+    blue: f64,
+    alpha: f64,
+}
+
+// From gtkenums.h, gtkdialog.h and gtkmessagedialog.h
+const DIALOG_MODAL: c_int = 1;
+const DIALOG_DESTROY_WITH_PARENT: c_int = 2;
+const MESSAGE_INFO: c_int = 0;
+const MESSAGE_WARNING: c_int = 1;
+const MESSAGE_QUESTION: c_int = 2;
+const MESSAGE_ERROR: c_int = 3;
+const BUTTONS_NONE: c_int = 0;
+const RESPONSE_ACCEPT: c_int = -3;
+const RESPONSE_DELETE_EVENT: c_int = -4;
+const RESPONSE_OK: c_int = -5;
+const RESPONSE_CANCEL: c_int = -6;
+const RESPONSE_YES: c_int = -8;
+const RESPONSE_NO: c_int = -9;
+const FILE_CHOOSER_OPEN: c_int = 0;
+const FILE_CHOOSER_SAVE: c_int = 1;
+const FILE_CHOOSER_SELECT_FOLDER: c_int = 2;
+const G_SOURCE_REMOVE: c_int = 0;
+const RTLD_NOW: c_int = 2;
+
+#[link(name = "dl")]
+extern "C" {
+    fn dlopen(filename: *const c_char, flags: c_int) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+}
+
+// The functions we use from libgtk-3 and the GLib libraries it links
+struct Library {
+    init_check: unsafe extern "C" fn(*mut c_int, *mut c_void) -> c_int,
+    main: unsafe extern "C" fn(),
+    get_major_version: unsafe extern "C" fn() -> c_uint,
+    get_minor_version: unsafe extern "C" fn() -> c_uint,
+    get_micro_version: unsafe extern "C" fn() -> c_uint,
+    display_get_default: unsafe extern "C" fn() -> Object,
+    main_context_default: unsafe extern "C" fn() -> Object,
+    main_context_is_owner: unsafe extern "C" fn(Object) -> c_int,
+    main_context_acquire: unsafe extern "C" fn(Object) -> c_int,
+    main_context_release: unsafe extern "C" fn(Object),
+    main_context_iteration: unsafe extern "C" fn(Object, c_int) -> c_int,
+    main_context_invoke:
+        unsafe extern "C" fn(Object, unsafe extern "C" fn(*mut c_void) -> c_int, *mut c_void),
+    free: unsafe extern "C" fn(*mut c_void),
+    list_free: unsafe extern "C" fn(*mut GList),
+    slist_free: unsafe extern "C" fn(*mut GSList),
+    type_check_instance_is_a: unsafe extern "C" fn(Object, usize) -> c_int,
+    window_list_toplevels: unsafe extern "C" fn() -> *mut GList,
+    window_is_active: unsafe extern "C" fn(Object) -> c_int,
+    window_set_title: unsafe extern "C" fn(Object, *const c_char),
+    window_set_modal: unsafe extern "C" fn(Object, c_int),
+    widget_get_window: unsafe extern "C" fn(Object) -> Object,
+    widget_show: unsafe extern "C" fn(Object),
+    widget_destroy: unsafe extern "C" fn(Object),
+    container_add: unsafe extern "C" fn(Object, Object),
+    message_dialog_new:
+        unsafe extern "C" fn(Object, c_int, c_int, c_int, *const c_char, ...) -> Object,
+    message_dialog_get_message_area: unsafe extern "C" fn(Object) -> Object,
+    dialog_add_button: unsafe extern "C" fn(Object, *const c_char, c_int) -> Object,
+    dialog_set_default_response: unsafe extern "C" fn(Object, c_int),
+    dialog_run: unsafe extern "C" fn(Object) -> c_int,
+    dialog_response: unsafe extern "C" fn(Object, c_int),
+    entry_new: unsafe extern "C" fn() -> Object,
+    entry_set_text: unsafe extern "C" fn(Object, *const c_char),
+    entry_get_text: unsafe extern "C" fn(Object) -> *const c_char,
+    entry_set_visibility: unsafe extern "C" fn(Object, c_int),
+    entry_set_activates_default: unsafe extern "C" fn(Object, c_int),
+    file_chooser_dialog_new:
+        unsafe extern "C" fn(*const c_char, Object, c_int, *const c_char, ...) -> Object,
+    file_chooser_set_select_multiple: unsafe extern "C" fn(Object, c_int),
+    file_chooser_set_do_overwrite_confirmation: unsafe extern "C" fn(Object, c_int),
+    file_chooser_set_current_folder: unsafe extern "C" fn(Object, *const c_char) -> c_int,
+    file_chooser_set_current_name: unsafe extern "C" fn(Object, *const c_char),
+    file_chooser_set_filename: unsafe extern "C" fn(Object, *const c_char) -> c_int,
+    file_chooser_add_filter: unsafe extern "C" fn(Object, Object),
+    file_chooser_set_filter: unsafe extern "C" fn(Object, Object),
+    file_chooser_get_filter: unsafe extern "C" fn(Object) -> Object,
+    file_chooser_get_filenames: unsafe extern "C" fn(Object) -> *mut GSList,
+    file_filter_new: unsafe extern "C" fn() -> Object,
+    file_filter_set_name: unsafe extern "C" fn(Object, *const c_char),
+    file_filter_add_pattern: unsafe extern "C" fn(Object, *const c_char),
+    color_chooser_dialog_new: unsafe extern "C" fn(*const c_char, Object) -> Object,
+    color_chooser_set_use_alpha: unsafe extern "C" fn(Object, c_int),
+    color_chooser_set_rgba: unsafe extern "C" fn(Object, *const GdkRgba),
+    color_chooser_get_rgba: unsafe extern "C" fn(Object, *mut GdkRgba),
+    // Only in GTK builds with the X11 backend
+    x11_window_get_type: Option<unsafe extern "C" fn() -> usize>,
+    x11_window_get_xid: Option<unsafe extern "C" fn(Object) -> c_ulong>,
+}
+
+// `F` is the function pointer type of `name`
+unsafe fn function<F: Copy>(handle: *mut c_void, name: &CStr) -> Option<F> {
+    let symbol = dlsym(handle, name.as_ptr());
+    if symbol.is_null() {
+        None
+    } else {
+        Some(mem::transmute_copy::<*mut c_void, F>(&symbol))
+    }
+}
+
+impl Library {
+    fn open() -> Option<Self> {
+        unsafe {
+            let name = CString::new(LIBRARY).ok()?;
+            let handle = dlopen(name.as_ptr(), RTLD_NOW);
+            if handle.is_null() {
+                return None;
+            }
+
+            Some(Self {
+                init_check: function(handle, c"gtk_init_check")?,
+                main: function(handle, c"gtk_main")?,
+                get_major_version: function(handle, c"gtk_get_major_version")?,
+                get_minor_version: function(handle, c"gtk_get_minor_version")?,
+                get_micro_version: function(handle, c"gtk_get_micro_version")?,
+                display_get_default: function(handle, c"gdk_display_get_default")?,
+                main_context_default: function(handle, c"g_main_context_default")?,
+                main_context_is_owner: function(handle, c"g_main_context_is_owner")?,
+                main_context_acquire: function(handle, c"g_main_context_acquire")?,
+                main_context_release: function(handle, c"g_main_context_release")?,
+                main_context_iteration: function(handle, c"g_main_context_iteration")?,
+                main_context_invoke: function(handle, c"g_main_context_invoke")?,
+                free: function(handle, c"g_free")?,
+                list_free: function(handle, c"g_list_free")?,
+                slist_free: function(handle, c"g_slist_free")?,
+                type_check_instance_is_a: function(handle, c"g_type_check_instance_is_a")?,
+                window_list_toplevels: function(handle, c"gtk_window_list_toplevels")?,
+                window_is_active: function(handle, c"gtk_window_is_active")?,
+                window_set_title: function(handle, c"gtk_window_set_title")?,
+                window_set_modal: function(handle, c"gtk_window_set_modal")?,
+                widget_get_window: function(handle, c"gtk_widget_get_window")?,
+                widget_show: function(handle, c"gtk_widget_show")?,
+                widget_destroy: function(handle, c"gtk_widget_destroy")?,
+                container_add: function(handle, c"gtk_container_add")?,
+                message_dialog_new: function(handle, c"gtk_message_dialog_new")?,
+                message_dialog_get_message_area: function(
+                    handle,
+                    c"gtk_message_dialog_get_message_area",
+                )?,
+                dialog_add_button: function(handle, c"gtk_dialog_add_button")?,
+                dialog_set_default_response: function(handle, c"gtk_dialog_set_default_response")?,
+                dialog_run: function(handle, c"gtk_dialog_run")?,
+                dialog_response: function(handle, c"gtk_dialog_response")?,
+                entry_new: function(handle, c"gtk_entry_new")?,
+                entry_set_text: function(handle, c"gtk_entry_set_text")?,
+                entry_get_text: function(handle, c"gtk_entry_get_text")?,
+                entry_set_visibility: function(handle, c"gtk_entry_set_visibility")?,
+                entry_set_activates_default: function(handle, c"gtk_entry_set_activates_default")?,
+                file_chooser_dialog_new: function(handle, c"gtk_file_chooser_dialog_new")?,
+                file_chooser_set_select_multiple: function(
+                    handle,
+                    c"gtk_file_chooser_set_select_multiple",
+                )?,
+                file_chooser_set_do_overwrite_confirmation: function(
+                    handle,
+                    c"gtk_file_chooser_set_do_overwrite_confirmation",
+                )?,
+                file_chooser_set_current_folder: function(
+                    handle,
+                    c"gtk_file_chooser_set_current_folder",
+                )?,
+                file_chooser_set_current_name: function(
+                    handle,
+                    c"gtk_file_chooser_set_current_name",
+                )?,
+                file_chooser_set_filename: function(handle, c"gtk_file_chooser_set_filename")?,
+                file_chooser_add_filter: function(handle, c"gtk_file_chooser_add_filter")?,
+                file_chooser_set_filter: function(handle, c"gtk_file_chooser_set_filter")?,
+                file_chooser_get_filter: function(handle, c"gtk_file_chooser_get_filter")?,
+                file_chooser_get_filenames: function(handle, c"gtk_file_chooser_get_filenames")?,
+                file_filter_new: function(handle, c"gtk_file_filter_new")?,
+                file_filter_set_name: function(handle, c"gtk_file_filter_set_name")?,
+                file_filter_add_pattern: function(handle, c"gtk_file_filter_add_pattern")?,
+                color_chooser_dialog_new: function(handle, c"gtk_color_chooser_dialog_new")?,
+                color_chooser_set_use_alpha: function(handle, c"gtk_color_chooser_set_use_alpha")?,
+                color_chooser_set_rgba: function(handle, c"gtk_color_chooser_set_rgba")?,
+                color_chooser_get_rgba: function(handle, c"gtk_color_chooser_get_rgba")?,
+                x11_window_get_type: function(handle, c"gdk_x11_window_get_type"),
+                x11_window_get_xid: function(handle, c"gdk_x11_window_get_xid"),
+            })
+        }
+    }
+
+    // The window to stack dialogs on: the parent if it is one of ours,
+    // otherwise the application's active window, if any
+    unsafe fn parent(&self, parent: Option<ParentWindow>) -> Object {
+        let toplevels = (self.window_list_toplevels)();
+        let mut active = ptr::null_mut();
+        let mut item = toplevels;
+
+        while !item.is_null() {
+            let window = (*item).data;
+            if let Some(ParentWindow::X11(xid)) = parent {
+                if self.xid(window) == Some(xid) {
+                    active = window;
+                    break;
+                }
+            }
+            if active.is_null() && (self.window_is_active)(window) != 0 {
+                active = window;
+            }
+            item = (*item).next;
+        }
+
+        (self.list_free)(toplevels);
+        active
+    }
+
+    unsafe fn xid(&self, window: Object) -> Option<u64> {
+        let (get_type, get_xid) = (self.x11_window_get_type?, self.x11_window_get_xid?);
+        let gdk_window = (self.widget_get_window)(window);
+        if gdk_window.is_null() || (self.type_check_instance_is_a)(gdk_window, get_type()) == 0 {
+            return None;
+        }
+        // c_ulong is only 32 bits wide on some targets
+        #[allow(clippy::unnecessary_cast)]
+        Some(get_xid(gdk_window) as u64)
+    }
+
+    unsafe fn message_dialog(
+        &self,
+        dialog: &Dialog,
+        message: &str,
+        icon: MessageBoxIcon,
+    ) -> Object {
+        let kind = match icon {
+            MessageBoxIcon::Info => MESSAGE_INFO,
+            MessageBoxIcon::Warning => MESSAGE_WARNING,
+            MessageBoxIcon::Error => MESSAGE_ERROR,
+            MessageBoxIcon::Question => MESSAGE_QUESTION,
+        };
+        let title = cstring(dialog.title());
+        let message = cstring(message);
+
+        let widget = (self.message_dialog_new)(
+            self.parent(dialog.parent()),
+            DIALOG_MODAL | DIALOG_DESTROY_WITH_PARENT,
+            kind,
+            BUTTONS_NONE,
+            c"%s".as_ptr(),
+            message.as_ptr(),
+        );
+        (self.window_set_title)(widget, title.as_ptr());
+        widget
+    }
+
+    unsafe fn add_button(&self, dialog: Object, label: &str, response: c_int) {
+        let label = cstring(label);
+        (self.dialog_add_button)(dialog, label.as_ptr(), response);
+    }
+
+    // A file chooser with Cancel and `accept` buttons and the filter groups
+    // of `dialog`, which are returned in the same order
+    unsafe fn file_chooser(
+        &self,
+        dialog: &FileDialog,
+        action: c_int,
+        accept: &str,
+    ) -> (Object, Vec<Object>) {
+        let title = cstring(dialog.title());
+        let widget = (self.file_chooser_dialog_new)(
+            title.as_ptr(),
+            self.parent(dialog.parent()),
+            action,
+            ptr::null::<c_char>(),
+        );
+        (self.window_set_modal)(widget, 1);
+        self.add_button(widget, "_Cancel", RESPONSE_CANCEL);
+        self.add_button(widget, accept, RESPONSE_ACCEPT);
+        (self.dialog_set_default_response)(widget, RESPONSE_ACCEPT);
+
+        let filters: Vec<Object> = dialog
+            .filters()
+            .iter()
+            .map(|filter| {
+                let gtk_filter = (self.file_filter_new)();
+                (self.file_filter_set_name)(gtk_filter, cstring(&filter.label()).as_ptr());
+                for pattern in filter.patterns() {
+                    (self.file_filter_add_pattern)(gtk_filter, cstring(pattern).as_ptr());
+                }
+                (self.file_chooser_add_filter)(widget, gtk_filter);
+                gtk_filter
+            })
+            .collect();
+
+        if let Some(&filter) = filters.get(dialog.default_filter()) {
+            (self.file_chooser_set_filter)(widget, filter);
+        }
+
+        (widget, filters)
+    }
+
+    // Opens the directory `path` or selects the file it names
+    unsafe fn start_at(&self, widget: Object, path: &Path) {
+        let path = absolute(path);
+        if path.is_dir() {
+            (self.file_chooser_set_current_folder)(widget, path_cstring(&path).as_ptr());
+        } else if path.exists() {
+            (self.file_chooser_set_filename)(widget, path_cstring(&path).as_ptr());
+        } else if let Some(dir) = path.parent() {
+            (self.file_chooser_set_current_folder)(widget, path_cstring(dir).as_ptr());
+        }
+    }
+
+    unsafe fn filenames(&self, widget: Object) -> Vec<PathBuf> {
+        let list = (self.file_chooser_get_filenames)(widget);
+        let mut paths = Vec::new();
+        let mut item = list;
+
+        while !item.is_null() {
+            let name = (*item).data;
+            let bytes = CStr::from_ptr(name.cast::<c_char>()).to_bytes();
+            paths.push(PathBuf::from(OsStr::from_bytes(bytes)));
+            (self.free)(name);
+            item = (*item).next;
+        }
+
+        (self.slist_free)(list);
+        paths
+    }
+}
+
+// GTK wants absolute paths, an empty one stands for the current directory
+fn absolute(path: &Path) -> PathBuf {
+    let current = env::current_dir().unwrap_or_default();
+    current.join(path)
+}
+
+fn cstring(text: &str) -> CString {
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+fn path_cstring(path: &Path) -> CString {
+    CString::new(path.as_os_str().as_bytes()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    #[ignore = "needs a display and libgtk-3"]
+    fn close_message_box_through_handle() {
+        assert!(Gtk.is_available(), "libgtk-3 or the display is missing");
+        let previous = backend::set_thread_backend(Some(Arc::new(Gtk)));
+
+        let mut handle = MessageBox::new("tfd", "Closed by the test").spawn();
+        thread::sleep(Duration::from_millis(500));
+        assert!(matches!(handle.try_wait(), Ok(None)));
+        handle.close();
+
+        let started = Instant::now();
+        let handle = MessageBox::new("tfd", "Timed out by the test")
+            .with_timeout(Duration::from_millis(500))
+            .spawn();
+        assert!(matches!(handle.wait(), Err(DialogError::TimedOut)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        backend::set_thread_backend(previous);
+    }
+}
@@ -0,0 +1,292 @@
+use super::*;
+
+pub struct Kdialog;
+
+impl ProgramBackend for Kdialog {
+    fn name(&self) -> &str {
+        "kdialog"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && command_exists("kdialog")
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("kdialog").await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notification_actions: false,
+            timeout: false,
+            ..Capabilities::all()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        require_display()?;
+        let icon_type = match msg_box.icon() {
+            MessageBoxIcon::Info => "dialog-information",
+            MessageBoxIcon::Warning => "dialog-warning",
+            MessageBoxIcon::Error => "dialog-error",
+            MessageBoxIcon::Question => "dialog-question",
+        };
+
+        confirm(
+            kdialog(msg_box.parent())
+                .arg("--msgbox")
+                .arg(msg_box.message())
+                .arg("--title")
+                .arg(msg_box.title())
+                .arg("--icon")
+                .arg(icon_type),
+        )
+        .await?;
+        Ok(())
+    }
+
+    // kdialog has no option to focus another button than the first, so the
+    // default answer is left out here and in the other fixed button sets
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        require_display()?;
+        let accepted = confirm(
+            kdialog(msg_box.parent())
+                .arg("--yesno")
+                .arg(msg_box.message())
+                .arg("--title")
+                .arg(msg_box.title())
+                .arg("--yes-label")
+                .arg("Ok")
+                .arg("--no-label")
+                .arg("Cancel"),
+        )
+        .await?;
+
+        Ok(if accepted {
+            OkCancel::Ok
+        } else {
+            OkCancel::Cancel
+        })
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        require_display()?;
+        let accepted = confirm(
+            kdialog(msg_box.parent())
+                .arg("--yesno")
+                .arg(msg_box.message())
+                .arg("--title")
+                .arg(msg_box.title()),
+        )
+        .await?;
+
+        Ok(if accepted { YesNo::Yes } else { YesNo::No })
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        require_display()?;
+        let mut cmd = kdialog(msg_box.parent());
+        cmd.arg("--yesnocancel")
+            .arg(msg_box.message())
+            .arg("--title")
+            .arg(msg_box.title());
+
+        let out = run_output(&mut cmd).await?;
+        match out.status.code() {
+            Some(0) => Ok(YesNoCancel::Yes),
+            Some(1) => Ok(YesNoCancel::No),
+            Some(2) => Ok(YesNoCancel::Cancel),
+            code => Err(failed(&cmd, code, &out.stderr)),
+        }
+    }
+
+    // kdialog has message boxes with up to three relabelled buttons, more
+    // are offered as a menu
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        require_display()?;
+        let buttons = msg_box.buttons();
+        let mut cmd = kdialog(msg_box.parent());
+
+        match buttons {
+            [] | [_] => {
+                cmd.arg("--msgbox").arg(msg_box.message());
+                if let [ok] = buttons {
+                    cmd.arg("--ok-label").arg(ok);
+                }
+            }
+            [yes, no] => {
+                cmd.arg("--yesno")
+                    .arg(msg_box.message())
+                    .arg("--yes-label")
+                    .arg(yes)
+                    .arg("--no-label")
+                    .arg(no);
+            }
+            [yes, no, cancel] => {
+                cmd.arg("--yesnocancel")
+                    .arg(msg_box.message())
+                    .arg("--yes-label")
+                    .arg(yes)
+                    .arg("--no-label")
+                    .arg(no)
+                    .arg("--cancel-label")
+                    .arg(cancel);
+            }
+            _ => {
+                cmd.arg("--menu").arg(msg_box.message());
+                for (i, label) in buttons.iter().enumerate() {
+                    cmd.arg(i.to_string()).arg(label);
+                }
+                cmd.arg("--title").arg(msg_box.title());
+
+                let answer = read_answer(&mut cmd).await?;
+                return Ok(answer.and_then(|tag| tag.trim().parse().ok()));
+            }
+        }
+
+        cmd.arg("--title").arg(msg_box.title());
+
+        let out = run_output(&mut cmd).await?;
+        match out.status.code() {
+            Some(code @ 0..=2) if (code as usize) < buttons.len().max(1) => Ok(Some(code as usize)),
+            code => Err(failed(&cmd, code, &out.stderr)),
+        }
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        require_display()?;
+        let mut cmd = kdialog(input.parent());
+
+        if input.is_password() {
+            cmd.arg("--password").arg(input.message());
+        } else {
+            cmd.arg("--inputbox")
+                .arg(input.message())
+                .arg(input.default_value().unwrap_or(""));
+        }
+
+        cmd.arg("--title").arg(input.title());
+
+        read_answer(&mut cmd).await
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        require_display()?;
+        let mut cmd = kdialog(dialog.parent());
+        cmd.arg("--getsavefilename").arg(dialog.initial_path());
+
+        if !dialog.filters().is_empty() {
+            cmd.arg(kdialog_filter(dialog));
+        }
+
+        cmd.arg("--title").arg(dialog.title());
+
+        Ok(read_path(&mut cmd).await?.map(SaveResult::from))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        require_display()?;
+        let mut cmd = kdialog(dialog.parent());
+        cmd.arg("--getopenfilename").arg(dialog.initial_path());
+
+        if !dialog.filters().is_empty() {
+            cmd.arg(kdialog_filter(dialog));
+        }
+
+        if dialog.multiple_selection() {
+            cmd.arg("--multiple").arg("--separate-output");
+        }
+
+        cmd.arg("--title").arg(dialog.title());
+
+        read_paths(&mut cmd, b'\n').await
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        require_display()?;
+        read_path(
+            kdialog(dialog.parent())
+                .arg("--getexistingdirectory")
+                .arg(dialog.initial_path())
+                .arg("--title")
+                .arg(dialog.title()),
+        )
+        .await
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        require_display()?;
+        let answer = read_answer(
+            kdialog(chooser.parent())
+                .arg("--getcolor")
+                .arg("--default")
+                .arg(rgb_to_hex(&default_rgb(chooser)))
+                .arg("--title")
+                .arg(chooser.title()),
+        )
+        .await?;
+        color_answer("kdialog", answer)
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = notify_send(notification).await {
+            return result;
+        }
+
+        require_display()?;
+        run_checked(
+            Command::new("kdialog")
+                .arg("--passivepopup")
+                .arg(notification.message())
+                .arg("5") // Show for 5 seconds
+                .arg("--title")
+                .arg(notification.title()),
+        )
+        .await
+    }
+}
+
+// One "description (patterns)" line per group, kdialog starts on the first
+fn kdialog_filter(dialog: &FileDialog) -> String {
+    dialog
+        .filters_default_first()
+        .map(|filter| format!("{} ({})", filter.label(), filter.patterns().join(" ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// kdialog stacks itself above an X11 parent window with --attach
+fn kdialog(parent: Option<ParentWindow>) -> Command {
+    let mut cmd = Command::new("kdialog");
+    if let Some(ParentWindow::X11(xid)) = parent {
+        cmd.arg("--attach").arg(xid.to_string());
+    }
+    cmd
+}
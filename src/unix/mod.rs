@@ -0,0 +1,739 @@
+use super::*;
+use crate::backend::{AsyncDialogs, DialogResult};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::future::Future;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, ExitStatus, Output, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+mod console;
+mod dialog;
+#[cfg(feature = "gtk")]
+mod gtk;
+mod kdialog;
+#[cfg(feature = "portal")]
+mod portal;
+mod session;
+mod tkinter;
+mod whiptail;
+#[cfg(feature = "x11")]
+mod x11;
+mod xdialog;
+mod yad;
+mod zenity;
+
+use session::{Desktop, Session};
+
+// Built-in backends in order of preference: the desktop's own dialogs
+// first, and the terminal first when there is no display to show windows on
+pub fn builtin_backends() -> Vec<Arc<dyn DialogBackend>> {
+    let session = Session::detect();
+
+    let mut graphical: Vec<Arc<dyn DialogBackend>> = match session.desktop {
+        Desktop::Kde | Desktop::Lxqt => vec![
+            Arc::new(Program(kdialog::Kdialog)),
+            Arc::new(Program(zenity::Zenity::QARMA)),
+            Arc::new(Program(zenity::Zenity::ZENITY)),
+            Arc::new(Program(zenity::Zenity::MATEDIALOG)),
+        ],
+        Desktop::Mate => vec![
+            Arc::new(Program(zenity::Zenity::MATEDIALOG)),
+            Arc::new(Program(zenity::Zenity::ZENITY)),
+            Arc::new(Program(kdialog::Kdialog)),
+            Arc::new(Program(zenity::Zenity::QARMA)),
+        ],
+        Desktop::Gtk | Desktop::Unknown => vec![
+            Arc::new(Program(zenity::Zenity::ZENITY)),
+            Arc::new(Program(zenity::Zenity::MATEDIALOG)),
+            Arc::new(Program(kdialog::Kdialog)),
+            Arc::new(Program(zenity::Zenity::QARMA)),
+        ],
+    };
+    #[cfg(feature = "gtk")]
+    graphical.insert(0, Arc::new(gtk::Gtk));
+    graphical.push(Arc::new(Program(yad::Yad)));
+    graphical.push(Arc::new(Program(xdialog::Xdialog)));
+    graphical.push(Arc::new(Program(tkinter::Tkinter)));
+    #[cfg(feature = "x11")]
+    graphical.push(Arc::new(x11::X11));
+    // Sandboxed applications cannot start the dialog programs, the portal
+    // shows the desktop's file chooser from outside the sandbox
+    #[cfg(feature = "portal")]
+    if session.sandboxed {
+        graphical.insert(0, Arc::new(portal::Portal::default()));
+    } else {
+        graphical.push(Arc::new(portal::Portal::default()));
+    }
+
+    let mut terminal: Vec<Arc<dyn DialogBackend>> = vec![
+        Arc::new(Program(dialog::Cdialog)),
+        Arc::new(Program(whiptail::Whiptail)),
+        Arc::new(console::Console),
+    ];
+
+    if session.graphical {
+        graphical.append(&mut terminal);
+        graphical
+    } else {
+        terminal.append(&mut graphical);
+        terminal
+    }
+}
+
+// A backend that shows its dialogs with a program such as zenity. Its
+// dialogs are futures, which the `*_async` methods await without a thread:
+// async-process picks up the exit through a pidfd or SIGCHLD and reads the
+// answer without blocking. `Program` makes it a `DialogBackend`.
+trait ProgramBackend: Send + Sync + 'static {
+    fn name(&self) -> &str;
+
+    fn is_available(&self) -> bool;
+
+    fn uses_terminal(&self) -> bool {
+        false
+    }
+
+    fn version(&self) -> impl Future<Output = Option<String>> + Send;
+
+    fn capabilities(&self) -> Capabilities;
+
+    fn message_box_ok(
+        &self,
+        msg_box: &MessageBox,
+    ) -> impl Future<Output = Result<(), DialogError>> + Send;
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> impl Future<Output = Result<OkCancel, DialogError>> + Send;
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> impl Future<Output = Result<YesNo, DialogError>> + Send;
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> impl Future<Output = Result<YesNoCancel, DialogError>> + Send;
+
+    fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> impl Future<Output = Result<Option<usize>, DialogError>> + Send;
+
+    fn input_box(
+        &self,
+        input: &InputBox,
+    ) -> impl Future<Output = Result<Option<String>, DialogError>> + Send;
+
+    fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> impl Future<Output = Result<Option<SaveResult>, DialogError>> + Send;
+
+    fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> impl Future<Output = Result<Option<Vec<PathBuf>>, DialogError>> + Send;
+
+    fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> impl Future<Output = Result<Option<PathBuf>, DialogError>> + Send;
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> impl Future<Output = Result<Option<(String, [u8; 3])>, DialogError>> + Send;
+
+    fn notification(
+        &self,
+        notification: &Notification,
+    ) -> impl Future<Output = Result<(), DialogError>> + Send;
+}
+
+// The blocking API drives the dialog futures on the calling thread, where
+// closing the dialog through its handle drops them and kills the program
+struct Program<P>(P);
+
+impl<P: ProgramBackend> DialogBackend for Program<P> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn is_available(&self) -> bool {
+        self.0.is_available()
+    }
+
+    fn uses_terminal(&self) -> bool {
+        self.0.uses_terminal()
+    }
+
+    fn version(&self) -> Option<String> {
+        async_io::block_on(self.0.version())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.0.capabilities()
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        handle::block_on(self.0.message_box_ok(msg_box))
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        handle::block_on(self.0.message_box_ok_cancel(msg_box, default))
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        handle::block_on(self.0.message_box_yes_no(msg_box, default))
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        handle::block_on(self.0.message_box_yes_no_cancel(msg_box, default))
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        handle::block_on(self.0.message_box_buttons(msg_box))
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        handle::block_on(self.0.input_box(input))
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        handle::block_on(self.0.save_file_dialog(dialog))
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        handle::block_on(self.0.open_file_dialog(dialog))
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        handle::block_on(self.0.select_folder_dialog(dialog))
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        handle::block_on(self.0.color_chooser_dialog(chooser))
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        handle::block_on(self.0.notification(notification))
+    }
+
+    fn as_async(&self) -> Option<&dyn AsyncDialogs> {
+        Some(self)
+    }
+}
+
+// The blocking API leaves timeouts to the watchdog of `handle`, futures
+// race their own timer
+impl<P: ProgramBackend> AsyncDialogs for Program<P> {
+    fn message_box_ok<'a>(&'a self, msg_box: &'a MessageBox) -> DialogResult<'a, ()> {
+        Box::pin(timed(
+            msg_box.dialog.timeout(),
+            self.0.message_box_ok(msg_box),
+        ))
+    }
+
+    fn message_box_ok_cancel<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: OkCancel,
+    ) -> DialogResult<'a, OkCancel> {
+        Box::pin(timed(
+            msg_box.dialog.timeout(),
+            self.0.message_box_ok_cancel(msg_box, default),
+        ))
+    }
+
+    fn message_box_yes_no<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: YesNo,
+    ) -> DialogResult<'a, YesNo> {
+        Box::pin(timed(
+            msg_box.dialog.timeout(),
+            self.0.message_box_yes_no(msg_box, default),
+        ))
+    }
+
+    fn message_box_yes_no_cancel<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+        default: YesNoCancel,
+    ) -> DialogResult<'a, YesNoCancel> {
+        Box::pin(timed(
+            msg_box.dialog.timeout(),
+            self.0.message_box_yes_no_cancel(msg_box, default),
+        ))
+    }
+
+    fn message_box_buttons<'a>(
+        &'a self,
+        msg_box: &'a MessageBox,
+    ) -> DialogResult<'a, Option<usize>> {
+        Box::pin(timed(
+            msg_box.dialog.timeout(),
+            self.0.message_box_buttons(msg_box),
+        ))
+    }
+
+    fn input_box<'a>(&'a self, input: &'a InputBox) -> DialogResult<'a, Option<String>> {
+        Box::pin(timed(input.dialog.timeout(), self.0.input_box(input)))
+    }
+
+    fn save_file_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<SaveResult>> {
+        Box::pin(timed(
+            dialog.dialog.timeout(),
+            self.0.save_file_dialog(dialog),
+        ))
+    }
+
+    fn open_file_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<Vec<PathBuf>>> {
+        Box::pin(timed(
+            dialog.dialog.timeout(),
+            self.0.open_file_dialog(dialog),
+        ))
+    }
+
+    fn select_folder_dialog<'a>(
+        &'a self,
+        dialog: &'a FileDialog,
+    ) -> DialogResult<'a, Option<PathBuf>> {
+        Box::pin(timed(
+            dialog.dialog.timeout(),
+            self.0.select_folder_dialog(dialog),
+        ))
+    }
+
+    fn color_chooser_dialog<'a>(
+        &'a self,
+        chooser: &'a ColorChooser,
+    ) -> DialogResult<'a, Option<(String, [u8; 3])>> {
+        Box::pin(self.0.color_chooser_dialog(chooser))
+    }
+
+    fn notification<'a>(&'a self, notification: &'a Notification) -> DialogResult<'a, ()> {
+        Box::pin(self.0.notification(notification))
+    }
+}
+
+// Gives up on the dialog like the watchdog does, after its timeout and a
+// grace period for programs that time out by themselves
+async fn timed<T>(
+    timeout: Option<Duration>,
+    dialog: impl Future<Output = Result<T, DialogError>>,
+) -> Result<T, DialogError> {
+    let Some(timeout) = timeout else {
+        return dialog.await;
+    };
+
+    let expired = async {
+        async_io::Timer::after(timeout + handle::WATCHDOG_GRACE).await;
+        Err(DialogError::TimedOut)
+    };
+    futures_lite::future::or(dialog, expired).await
+}
+
+// Custom buttons on top of the fixed button sets, like
+// `backend::emulate_buttons`
+async fn emulate_buttons<P: ProgramBackend>(
+    backend: &P,
+    msg_box: &MessageBox,
+) -> Result<Option<usize>, DialogError> {
+    match msg_box.buttons().len() {
+        0 | 1 => backend.message_box_ok(msg_box).await.map(|()| Some(0)),
+        2 => Ok(Some(
+            match backend.message_box_ok_cancel(msg_box, OkCancel::Ok).await? {
+                OkCancel::Ok => 0,
+                OkCancel::Cancel => 1,
+            },
+        )),
+        3 => Ok(Some(
+            match backend
+                .message_box_yes_no_cancel(msg_box, YesNoCancel::Yes)
+                .await?
+            {
+                YesNoCancel::Yes => 0,
+                YesNoCancel::No => 1,
+                YesNoCancel::Cancel => 2,
+            },
+        )),
+        n => Err(DialogError::Unsupported(format!(
+            "{} buttons in a message box",
+            n
+        ))),
+    }
+}
+
+// What we found out about dialog programs and the session so far, see
+// `refresh_backends`
+#[derive(Default)]
+struct Programs {
+    installed: HashMap<String, bool>,
+    versions: HashMap<String, Option<String>>,
+    display: Option<bool>,
+}
+
+fn programs() -> MutexGuard<'static, Programs> {
+    static PROGRAMS: OnceLock<Mutex<Programs>> = OnceLock::new();
+    PROGRAMS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn forget_programs() {
+    *programs() = Programs::default();
+}
+
+fn command_exists(cmd: &str) -> bool {
+    *programs()
+        .installed
+        .entry(cmd.to_string())
+        .or_insert_with(|| find_in_path(cmd))
+}
+
+// Like which(1): an executable file in one of the PATH directories. Empty
+// entries would mean the current directory and are skipped.
+fn find_in_path(cmd: &str) -> bool {
+    let Some(path) = env::var_os("PATH") else {
+        return false;
+    };
+    env::split_paths(&path)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .any(|dir| {
+            fs::metadata(dir.join(cmd))
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        })
+}
+
+async fn program_version(program: &str) -> Option<String> {
+    if let Some(version) = programs().versions.get(program) {
+        return version.clone();
+    }
+
+    // Not under the lock, the program may take a moment to answer
+    let version = query_version(program).await;
+    programs()
+        .versions
+        .insert(program.to_string(), version.clone());
+    version
+}
+
+// First number on the first line `program --version` prints, which is the
+// bare version for "4.0.1", "kdialog 23.08.5", "Version: 1.3-20220728" and
+// "12.3 (GTK+ 3.24.38)"
+async fn query_version(program: &str) -> Option<String> {
+    let out = async_process::Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .ok()?;
+    if !out.status.success() {
+        return None;
+    }
+
+    // Some programs print their version on stderr
+    [out.stdout, out.stderr].iter().find_map(|text| {
+        let text = String::from_utf8_lossy(text);
+        let line = text.lines().find(|line| !line.trim().is_empty())?;
+        line.split_whitespace()
+            .find(|word| word.starts_with(|c: char| c.is_ascii_digit()))
+            .or_else(|| line.split_whitespace().last())
+            .map(str::to_string)
+    })
+}
+
+// Graphical programs cannot do anything useful without a display
+fn has_display() -> bool {
+    *programs()
+        .display
+        .get_or_insert_with(|| Session::detect().graphical)
+}
+
+fn require_display() -> Result<(), DialogError> {
+    if has_display() {
+        Ok(())
+    } else {
+        Err(DialogError::NoDisplay)
+    }
+}
+
+fn program_name(cmd: &Command) -> String {
+    cmd.get_program().to_string_lossy().into_owned()
+}
+
+fn spawn_error(cmd: &Command, err: io::Error) -> DialogError {
+    if err.kind() == io::ErrorKind::NotFound {
+        DialogError::NotInstalled(program_name(cmd))
+    } else {
+        DialogError::Spawn {
+            program: program_name(cmd),
+            source: err,
+        }
+    }
+}
+
+// The program of `cmd` as a child to await. It is killed when the future
+// awaiting it is dropped, which is how dialogs are closed.
+fn child_command(cmd: &Command) -> async_process::Command {
+    let mut child = async_process::Command::new(cmd.get_program());
+    child.args(cmd.get_args()).kill_on_drop(true);
+    for (key, value) in cmd.get_envs() {
+        match value {
+            Some(value) => child.env(key, value),
+            None => child.env_remove(key),
+        };
+    }
+    if let Some(dir) = cmd.get_current_dir() {
+        child.current_dir(dir);
+    }
+    child
+}
+
+// Run a program that shares the terminal with us (dialog draws on it)
+async fn run_status(cmd: &mut Command) -> Result<ExitStatus, DialogError> {
+    let mut child = child_command(cmd)
+        .spawn()
+        .map_err(|err| spawn_error(cmd, err))?;
+    child.status().await.map_err(DialogError::Io)
+}
+
+// Run a dialog(1) style program that draws with our stdio and prints its
+// answer on stderr, `None` when cancelled or escaped
+async fn read_stderr_answer(cmd: &mut Command) -> Result<Option<Vec<u8>>, DialogError> {
+    let child = child_command(cmd)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| spawn_error(cmd, err))?;
+    let out = child.output().await.map_err(DialogError::Io)?;
+    match out.status.code() {
+        Some(0) => {
+            let mut answer = out.stderr;
+            while answer.last() == Some(&b'\n') {
+                answer.pop();
+            }
+            Ok(Some(answer))
+        }
+        Some(1) | Some(255) => Ok(None),
+        code => Err(failed(cmd, code, &out.stderr)),
+    }
+}
+
+// Run a program and capture what it prints
+async fn run_output(cmd: &mut Command) -> Result<Output, DialogError> {
+    let child = child_command(cmd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| spawn_error(cmd, err))?;
+    child.output().await.map_err(DialogError::Io)
+}
+
+// Exit status of zenity and dialog when their `--timeout` runs out
+const TIMEOUT_STATUS: i32 = 5;
+
+// Whole seconds for the `--timeout` options, rounded up
+fn timeout_secs(timeout: Duration) -> u64 {
+    let secs = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+    secs.max(1)
+}
+
+fn failed(cmd: &Command, code: Option<i32>, stderr: &[u8]) -> DialogError {
+    let has_timeout = cmd
+        .get_args()
+        .any(|arg| arg.as_bytes().starts_with(b"--timeout"));
+    if has_timeout && code == Some(TIMEOUT_STATUS) {
+        return DialogError::TimedOut;
+    }
+
+    DialogError::Failed {
+        program: program_name(cmd),
+        code,
+        message: String::from_utf8_lossy(stderr).trim().to_string(),
+    }
+}
+
+// Exit status 0 accepts, 1 declines, everything else is an error
+async fn confirm(cmd: &mut Command) -> Result<bool, DialogError> {
+    let out = run_output(cmd).await?;
+    match out.status.code() {
+        Some(0) => Ok(true),
+        Some(1) => Ok(false),
+        code => Err(failed(cmd, code, &out.stderr)),
+    }
+}
+
+// Run a program whose only answer is whether it succeeded
+async fn run_checked(cmd: &mut Command) -> Result<(), DialogError> {
+    let out = run_output(cmd).await?;
+    if out.status.success() {
+        Ok(())
+    } else {
+        Err(failed(cmd, out.status.code(), &out.stderr))
+    }
+}
+
+// Same as `confirm` for programs that need the terminal; dialog(1) also
+// reports Escape as 255
+async fn confirm_terminal(cmd: &mut Command) -> Result<bool, DialogError> {
+    let status = run_status(cmd).await?;
+    match status.code() {
+        Some(0) => Ok(true),
+        Some(1) | Some(255) => Ok(false),
+        code => Err(failed(cmd, code, &[])),
+    }
+}
+
+// Run a program that prints its answer, `None` when the user cancelled
+async fn read_answer(cmd: &mut Command) -> Result<Option<String>, DialogError> {
+    let answer = read_raw_answer(cmd).await?;
+    Ok(answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+// Same as `read_answer` but keeps the bytes, file names need not be UTF-8
+async fn read_raw_answer(cmd: &mut Command) -> Result<Option<Vec<u8>>, DialogError> {
+    let out = run_output(cmd).await?;
+    match out.status.code() {
+        Some(0) => {
+            let mut stdout = out.stdout;
+            while stdout.last() == Some(&b'\n') {
+                stdout.pop();
+            }
+            Ok(Some(stdout))
+        }
+        Some(1) => Ok(None),
+        code => Err(failed(cmd, code, &out.stderr)),
+    }
+}
+
+async fn read_path(cmd: &mut Command) -> Result<Option<PathBuf>, DialogError> {
+    let answer = read_raw_answer(cmd).await?;
+    Ok(answer
+        .filter(|bytes| !bytes.is_empty())
+        .map(|bytes| PathBuf::from(OsStr::from_bytes(&bytes))))
+}
+
+// Read several file names printed on one line or one per line
+async fn read_paths(cmd: &mut Command, separator: u8) -> Result<Option<Vec<PathBuf>>, DialogError> {
+    let answer = read_raw_answer(cmd).await?;
+    Ok(answer.filter(|bytes| !bytes.is_empty()).map(|bytes| {
+        bytes
+            .split(|&b| b == separator)
+            .filter(|path| !path.is_empty())
+            .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+            .collect()
+    }))
+}
+
+fn read_console_line(prompt: &str) -> Result<Option<String>, DialogError> {
+    print!("{}", prompt);
+    std::io::stdout().flush().map_err(DialogError::Io)?;
+
+    let mut input = String::new();
+    match std::io::stdin().read_line(&mut input) {
+        Ok(0) => Ok(None),
+        Ok(_) => Ok(Some(input.trim().to_string())),
+        Err(err) => Err(DialogError::Io(err)),
+    }
+}
+
+fn default_rgb(chooser: &ColorChooser) -> [u8; 3] {
+    match chooser.default_color() {
+        DefaultColorValue::Hex(hex) => super::hex_to_rgb(hex),
+        DefaultColorValue::RGB(rgb) => *rgb,
+    }
+}
+
+// Parse "#rrggbb", "rgb(r,g,b)" or "rgba(r,g,b,a)" as printed by zenity and kdialog
+fn parse_color(color: &str) -> Option<[u8; 3]> {
+    if let Some(hex) = color.strip_prefix('#') {
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return Some(super::hex_to_rgb(hex));
+    }
+
+    let inner = color
+        .strip_prefix("rgba(")
+        .or_else(|| color.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+
+    let parts: Vec<&str> = inner.split(',').collect();
+    if parts.len() < 3 {
+        return None;
+    }
+
+    let r = parts[0].trim().parse::<u8>().ok()?;
+    let g = parts[1].trim().parse::<u8>().ok()?;
+    let b = parts[2].trim().parse::<u8>().ok()?;
+    Some([r, g, b])
+}
+
+fn color_answer(
+    program: &str,
+    answer: Option<String>,
+) -> Result<Option<(String, [u8; 3])>, DialogError> {
+    match answer {
+        Some(color) => match parse_color(color.trim()) {
+            Some(rgb) => Ok(Some((super::rgb_to_hex(&rgb), rgb))),
+            None => Err(DialogError::InvalidOutput {
+                program: program.to_string(),
+                output: color,
+            }),
+        },
+        None => Ok(None),
+    }
+}
+
+// notify-send is the desktop-neutral way to show a notification, every
+// backend prefers it over its own popup
+async fn notify_send(notification: &Notification) -> Option<Result<(), DialogError>> {
+    if !command_exists("notify-send") {
+        return None;
+    }
+
+    Some(
+        run_checked(
+            Command::new("notify-send")
+                .arg(notification.title())
+                .arg(notification.message()),
+        )
+        .await,
+    )
+}
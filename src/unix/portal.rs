@@ -0,0 +1,643 @@
+use super::*;
+use std::str;
+use std::sync::atomic::{AtomicU32, Ordering};
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type as MessageType;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+use zbus::MatchRule;
+
+// File dialogs through the XDG desktop portal's FileChooser interface on
+// the session bus, which shows the desktop's own file chooser and is the
+// only one reachable from Flatpak and Snap sandboxes. The portal has no
+// message or input boxes, those go to the next available backend.
+#[derive(Default)]
+pub struct Portal {
+    // The session bus's portal unless a test starts a bus of its own
+    address: Option<String>,
+}
+
+impl Portal {
+    fn connect(&self) -> Result<Connection, DialogError> {
+        match &self.address {
+            Some(address) => zbus::blocking::connection::Builder::address(address.as_str())
+                .and_then(|builder| builder.build()),
+            None => Connection::session(),
+        }
+        .map_err(bus_error)
+    }
+
+    // Version of the FileChooser interface, `None` without a portal. The
+    // session bus's is cached with the installed programs, asking may start
+    // the portal.
+    fn portal_version(&self) -> Option<u32> {
+        if self.address.is_some() {
+            return query_portal_version(&self.connect().ok()?);
+        }
+        if let Some(version) = programs().versions.get(PORTAL) {
+            return version.as_deref().and_then(|version| version.parse().ok());
+        }
+
+        let version = query_portal_version(&self.connect().ok()?);
+        programs().versions.insert(
+            PORTAL.to_string(),
+            version.map(|version| version.to_string()),
+        );
+        version
+    }
+
+    // The backend automatic selection would pick without the portal, so
+    // registered backends and the user's preferences apply
+    fn fallback(&self) -> Result<Arc<dyn DialogBackend>, DialogError> {
+        backend::fallback_backend(self.name())
+    }
+}
+
+impl DialogBackend for Portal {
+    fn name(&self) -> &str {
+        "portal"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && self.portal_version().is_some()
+    }
+
+    fn version(&self) -> Option<String> {
+        self.portal_version().map(|version| version.to_string())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            file_dialogs: true,
+            multiple_selection: true,
+            filters: true,
+            ..self
+                .fallback()
+                .map(|b| b.capabilities())
+                .unwrap_or_default()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        self.fallback()?.message_box_ok(msg_box)
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        self.fallback()?.message_box_ok_cancel(msg_box, default)
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        self.fallback()?.message_box_yes_no(msg_box, default)
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        self.fallback()?.message_box_yes_no_cancel(msg_box, default)
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        self.fallback()?.message_box_buttons(msg_box)
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        self.fallback()?.input_box(input)
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        let mut options = filter_options(dialog);
+        let path = dialog.initial_path();
+        if path.is_dir() {
+            options.insert("current_folder", folder_value(path));
+        } else if path.is_file() {
+            options.insert("current_file", folder_value(path));
+        } else if let Some(name) = path.file_name() {
+            options.insert("current_name", name.to_string_lossy().into_owned().into());
+            if let Some(dir) = path.parent().filter(|dir| dir.is_dir()) {
+                options.insert("current_folder", folder_value(dir));
+            }
+        }
+
+        let conn = self.connect()?;
+        let Some(mut results) = request(&conn, "SaveFile", &dialog.dialog, options)? else {
+            return Ok(None);
+        };
+        let Some(path) = uris(&mut results)?.into_iter().next() else {
+            return Ok(None);
+        };
+        Ok(Some(SaveResult {
+            path,
+            filter_index: current_filter(dialog, &mut results),
+        }))
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        let mut options = filter_options(dialog);
+        options.insert("multiple", dialog.multiple_selection().into());
+        add_current_folder(&mut options, dialog);
+
+        let conn = self.connect()?;
+        let Some(mut results) = request(&conn, "OpenFile", &dialog.dialog, options)? else {
+            return Ok(None);
+        };
+        let paths = uris(&mut results)?;
+        Ok(Some(paths).filter(|paths| !paths.is_empty()))
+    }
+
+    // Folders can be picked from version 3 of the interface on, older
+    // portals would silently pick a file instead
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        if self.portal_version().is_some_and(|version| version < 3) {
+            return self.fallback()?.select_folder_dialog(dialog);
+        }
+
+        let mut options = HashMap::new();
+        options.insert("directory", true.into());
+        add_current_folder(&mut options, dialog);
+
+        let conn = self.connect()?;
+        let Some(mut results) = request(&conn, "OpenFile", &dialog.dialog, options)? else {
+            return Ok(None);
+        };
+        Ok(uris(&mut results)?.into_iter().next())
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        self.fallback()?.color_chooser_dialog(chooser)
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        self.fallback()?.notification(notification)
+    }
+}
+
+const PORTAL: &str = "xdg-desktop-portal";
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const DESKTOP_PATH: &str = "/org/freedesktop/portal/desktop";
+const FILE_CHOOSER: &str = "org.freedesktop.portal.FileChooser";
+const REQUEST: &str = "org.freedesktop.portal.Request";
+
+// Response codes of org.freedesktop.portal.Request
+const RESPONSE_SUCCESS: u32 = 0;
+const RESPONSE_CANCELLED: u32 = 1;
+
+// Glob patterns, as opposed to MIME types
+const FILTER_GLOB: u32 = 0;
+
+type Filter = (String, Vec<(u32, String)>);
+type Options = HashMap<&'static str, Value<'static>>;
+type Results = HashMap<String, OwnedValue>;
+
+fn query_portal_version(conn: &Connection) -> Option<u32> {
+    let reply = conn
+        .call_method(
+            Some(DESTINATION),
+            DESKTOP_PATH,
+            Some("org.freedesktop.DBus.Properties"),
+            "Get",
+            &(FILE_CHOOSER, "version"),
+        )
+        .ok()?;
+    let version: OwnedValue = reply.body().deserialize().ok()?;
+    u32::try_from(version).ok()
+}
+
+// Calls `method` of the FileChooser and waits for the user's answer, which
+// the portal sends as the Response signal of a request object. `None` when
+// the dialog was cancelled.
+fn request(
+    conn: &Connection,
+    method: &str,
+    dialog: &Dialog,
+    mut options: Options,
+) -> Result<Option<Results>, DialogError> {
+    static NEXT_TOKEN: AtomicU32 = AtomicU32::new(0);
+
+    // The request object's path is made of our bus name and a token of our
+    // choice. Listening on it before calling keeps a quick answer from
+    // being missed.
+    let token = format!("tfd{}", NEXT_TOKEN.fetch_add(1, Ordering::Relaxed));
+    let sender = conn
+        .unique_name()
+        .map(|name| name.trim_start_matches(':').replace('.', "_"))
+        .unwrap_or_default();
+    let path = format!("{}/request/{}/{}", DESKTOP_PATH, sender, token);
+    let mut responses = listen(conn, &path)?;
+    options.insert("handle_token", token.into());
+
+    let parent = match dialog.parent() {
+        Some(ParentWindow::X11(xid)) => format!("x11:{:x}", xid),
+        _ => String::new(),
+    };
+    let reply = conn
+        .call_method(
+            Some(DESTINATION),
+            DESKTOP_PATH,
+            Some(FILE_CHOOSER),
+            method,
+            &(parent, dialog.title(), options),
+        )
+        .map_err(bus_error)?;
+    let handle: OwnedObjectPath = reply.body().deserialize().map_err(bus_error)?;
+
+    // Portals older than 0.9 pick the path themselves
+    if handle.as_str() != path {
+        responses = listen(conn, handle.as_str())?;
+    }
+
+    // Closing the handle closes the request, which ends without a
+    // response. Closing the connection then ends the wait below.
+    let _hook = handle::on_cancel({
+        let conn = conn.clone();
+        move || {
+            let _ = conn.call_method(Some(DESTINATION), &handle, Some(REQUEST), "Close", &());
+            let _ = conn.clone().close();
+        }
+    });
+
+    let message = match responses.next() {
+        Some(message) => message.map_err(bus_error)?,
+        None => return Err(DialogError::Closed),
+    };
+    let (response, results): (u32, Results) = message.body().deserialize().map_err(bus_error)?;
+    match response {
+        RESPONSE_SUCCESS => Ok(Some(results)),
+        RESPONSE_CANCELLED => Ok(None),
+        code => Err(DialogError::Failed {
+            program: PORTAL.to_string(),
+            code: i32::try_from(code).ok(),
+            message: "the file chooser ended without an answer".to_string(),
+        }),
+    }
+}
+
+// The Response signals of the request at `path`
+fn listen(conn: &Connection, path: &str) -> Result<MessageIterator, DialogError> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(REQUEST)
+        .and_then(|rule| rule.member("Response"))
+        .and_then(|rule| rule.path(path.to_string()))
+        .map_err(bus_error)?
+        .build();
+    MessageIterator::for_match_rule(rule, conn, None).map_err(bus_error)
+}
+
+fn bus_error(err: zbus::Error) -> DialogError {
+    DialogError::Failed {
+        program: PORTAL.to_string(),
+        code: None,
+        message: err.to_string(),
+    }
+}
+
+// The filter groups, with the initially active one
+fn filter_options(dialog: &FileDialog) -> Options {
+    let mut options = Options::new();
+    let filters: Vec<Filter> = dialog.filters().iter().map(portal_filter).collect();
+    if let Some(current) = filters.get(dialog.default_filter()) {
+        options.insert("current_filter", current.clone().into());
+        options.insert("filters", filters.into());
+    }
+    options
+}
+
+fn portal_filter(filter: &FileFilter) -> Filter {
+    let patterns = filter
+        .patterns()
+        .iter()
+        .map(|pattern| (FILTER_GLOB, pattern.clone()))
+        .collect();
+    (filter.label(), patterns)
+}
+
+// The directory of the initial path, the path itself if it is one
+fn add_current_folder(options: &mut Options, dialog: &FileDialog) {
+    let path = dialog.initial_path();
+    let dir = if path.is_dir() {
+        Some(path)
+    } else {
+        path.parent()
+    };
+    if let Some(dir) = dir.filter(|dir| dir.is_dir()) {
+        options.insert("current_folder", folder_value(dir));
+    }
+}
+
+// Paths go over the bus as NUL-terminated bytes
+fn folder_value(path: &Path) -> Value<'static> {
+    let mut bytes = path.as_os_str().as_bytes().to_vec();
+    bytes.push(0);
+    bytes.into()
+}
+
+// Index of the filter group the user left active, matched by its name
+fn current_filter(dialog: &FileDialog, results: &mut Results) -> Option<usize> {
+    let current = Filter::try_from(results.remove("current_filter")?).ok()?;
+    dialog
+        .filters()
+        .iter()
+        .position(|filter| portal_filter(filter) == current)
+}
+
+fn uris(results: &mut Results) -> Result<Vec<PathBuf>, DialogError> {
+    let Some(uris) = results.remove("uris") else {
+        return Ok(Vec::new());
+    };
+    let uris = Vec::<String>::try_from(uris).map_err(|err| bus_error(err.into()))?;
+    uris.iter()
+        .map(|uri| {
+            uri_path(uri).ok_or_else(|| DialogError::InvalidOutput {
+                program: PORTAL.to_string(),
+                output: uri.clone(),
+            })
+        })
+        .collect()
+}
+
+// The path of a "file://" URI, with its %XX escapes decoded to bytes
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skips the host, usually empty
+    let path = &rest.as_bytes()[rest.find('/')?..];
+
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let escaped = path
+            .get(i + 1..i + 3)
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+        match (path[i], escaped) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                bytes.push(byte);
+                i += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(OsStr::from_bytes(&bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::testing::MockBackend;
+    use std::io::{BufRead, BufReader};
+    use std::process::Child;
+    use zbus::message::Header;
+    use zbus::zvariant::ObjectPath;
+
+    fn filters_dialog() -> FileDialog {
+        FileDialog::new("Pick")
+            .with_filter(&["*.txt"], "Text")
+            .with_filter(&["*.png", "*.jpg"], "Images")
+            .with_default_filter(1)
+    }
+
+    #[test]
+    fn uri_paths() {
+        assert_eq!(
+            uri_path("file:///tmp/a%20b.txt"),
+            Some(PathBuf::from("/tmp/a b.txt"))
+        );
+        assert_eq!(uri_path("file://host/etc"), Some(PathBuf::from("/etc")));
+        assert_eq!(
+            uri_path("file:///100%25%zz%4"),
+            Some(PathBuf::from("/100%%zz%4"))
+        );
+        assert_eq!(
+            uri_path("file:///caf%E9").map(|path| path.as_os_str().as_bytes().to_vec()),
+            Some(b"/caf\xe9".to_vec())
+        );
+        assert_eq!(uri_path("https://example.com/a"), None);
+        assert_eq!(uri_path("file://host"), None);
+    }
+
+    #[test]
+    fn filters_start_on_the_default() {
+        let options = filter_options(&filters_dialog());
+        let current = Filter::try_from(options["current_filter"].try_clone().unwrap()).unwrap();
+        assert_eq!(
+            current,
+            (
+                "Images".to_string(),
+                vec![
+                    (FILTER_GLOB, "*.png".to_string()),
+                    (FILTER_GLOB, "*.jpg".to_string())
+                ]
+            )
+        );
+        let filters = Vec::<Filter>::try_from(options["filters"].try_clone().unwrap()).unwrap();
+        assert_eq!(filters.len(), 2);
+
+        assert!(filter_options(&FileDialog::new("Pick")).is_empty());
+    }
+
+    #[test]
+    fn current_filter_by_name() {
+        let dialog = filters_dialog();
+        let mut results = Results::new();
+        let picked = portal_filter(&dialog.filters()[0]);
+        results.insert(
+            "current_filter".to_string(),
+            Value::from(picked).try_into().unwrap(),
+        );
+        assert_eq!(current_filter(&dialog, &mut results), Some(0));
+        assert_eq!(current_filter(&dialog, &mut results), None);
+
+        let unknown: Filter = ("Other".to_string(), vec![(FILTER_GLOB, "*".to_string())]);
+        results.insert(
+            "current_filter".to_string(),
+            Value::from(unknown).try_into().unwrap(),
+        );
+        assert_eq!(current_filter(&dialog, &mut results), None);
+    }
+
+    #[test]
+    fn falls_back_on_the_next_backend() {
+        let backends: Vec<Arc<dyn DialogBackend>> = vec![
+            Arc::new(Portal::default()),
+            Arc::new(console::Console),
+            Arc::new(MockBackend::new()),
+        ];
+        let fallback = |config: &Config| {
+            backend::fallback_from(backends.clone(), config, "portal").map(|b| b.name().to_string())
+        };
+
+        // Registration order, skipping the portal itself
+        assert_eq!(fallback(&Config::default()).unwrap(), "console");
+        let preferred = Config {
+            backends: vec!["portal".to_string(), "mock".to_string()],
+            ..Config::default()
+        };
+        assert_eq!(fallback(&preferred).unwrap(), "mock");
+        let forbidden = Config {
+            forbid_console: true,
+            ..Config::default()
+        };
+        assert_eq!(fallback(&forbidden).unwrap(), "mock");
+
+        let alone = backend::fallback_from(backends[..1].to_vec(), &Config::default(), "portal");
+        assert!(matches!(alone, Err(DialogError::NoBackend)));
+    }
+
+    // A session bus of our own, gone with the value
+    struct Bus(Child);
+
+    impl Bus {
+        fn start() -> Option<(Self, String)> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some((Self(daemon), address.trim().to_string()))
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    // Answers every request right away with `response` and the given URIs,
+    // passing the filter it was started on back as the one left active
+    struct MockChooser {
+        response: u32,
+        uris: Vec<String>,
+    }
+
+    impl MockChooser {
+        async fn answer(
+            &self,
+            header: Header<'_>,
+            conn: &zbus::Connection,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            let sender = header
+                .sender()
+                .map(|name| name.as_str())
+                .unwrap_or_default();
+            let token = options
+                .get("handle_token")
+                .and_then(|token| String::try_from(token.try_clone().ok()?).ok())
+                .unwrap_or_default();
+            let path = format!(
+                "{}/request/{}/{}",
+                DESKTOP_PATH,
+                sender.trim_start_matches(':').replace('.', "_"),
+                token
+            );
+            let path = ObjectPath::try_from(path).map_err(zbus::Error::from)?;
+
+            let mut results: HashMap<&str, Value<'_>> = HashMap::new();
+            results.insert("uris", self.uris.clone().into());
+            if let Some(filter) = options.get("current_filter") {
+                results.insert(
+                    "current_filter",
+                    Value::from(filter.try_clone().map_err(zbus::Error::from)?),
+                );
+            }
+            conn.emit_signal(
+                None::<()>,
+                &path,
+                REQUEST,
+                "Response",
+                &(self.response, results),
+            )
+            .await?;
+            Ok(path.into())
+        }
+    }
+
+    #[zbus::interface(name = "org.freedesktop.portal.FileChooser")]
+    impl MockChooser {
+        #[zbus(property, name = "version")]
+        fn version(&self) -> u32 {
+            4
+        }
+
+        async fn open_file(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _parent: String,
+            _title: String,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            self.answer(header, conn, options).await
+        }
+
+        async fn save_file(
+            &self,
+            #[zbus(header)] header: Header<'_>,
+            #[zbus(connection)] conn: &zbus::Connection,
+            _parent: String,
+            _title: String,
+            options: HashMap<String, OwnedValue>,
+        ) -> zbus::fdo::Result<OwnedObjectPath> {
+            self.answer(header, conn, options).await
+        }
+    }
+
+    fn serve(address: &str, response: u32, uris: &[&str]) -> Connection {
+        let chooser = MockChooser {
+            response,
+            uris: uris.iter().map(|uri| uri.to_string()).collect(),
+        };
+        zbus::blocking::connection::Builder::address(address)
+            .and_then(|builder| builder.name(DESTINATION))
+            .and_then(|builder| builder.serve_at(DESKTOP_PATH, chooser))
+            .and_then(|builder| builder.build())
+            .unwrap()
+    }
+
+    #[test]
+    #[ignore = "needs dbus-daemon"]
+    fn file_chooser_on_mock_portal() {
+        let (_bus, address) = Bus::start().expect("cannot start dbus-daemon");
+        let portal = Portal {
+            address: Some(address.clone()),
+        };
+
+        let chooser = serve(&address, RESPONSE_SUCCESS, &["file:///tmp/a%20b.txt"]);
+        assert_eq!(portal.portal_version(), Some(4));
+        let opened = portal.open_file_dialog(&filters_dialog()).unwrap();
+        assert_eq!(opened, Some(vec![PathBuf::from("/tmp/a b.txt")]));
+
+        let saved = portal.save_file_dialog(&filters_dialog()).unwrap().unwrap();
+        assert_eq!(saved.path, PathBuf::from("/tmp/a b.txt"));
+        assert_eq!(saved.filter_index, Some(1));
+
+        let folder = portal.select_folder_dialog(&FileDialog::new("Folder"));
+        assert_eq!(folder.unwrap(), Some(PathBuf::from("/tmp/a b.txt")));
+        drop(chooser);
+
+        let _chooser = serve(&address, RESPONSE_CANCELLED, &[]);
+        assert_eq!(portal.open_file_dialog(&filters_dialog()).unwrap(), None);
+    }
+}
@@ -0,0 +1,217 @@
+use std::env;
+
+// Desktop environments whose native dialogs we know about
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Desktop {
+    Kde,
+    Lxqt,
+    Mate,
+    // GNOME and the other GTK based desktops
+    Gtk,
+    Unknown,
+}
+
+// The session dialogs are shown in, as far as the environment tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Session {
+    pub desktop: Desktop,
+    // Whether a graphical display is reachable at all
+    pub graphical: bool,
+    // Inside a Flatpak or Snap sandbox, where dialog programs are out of
+    // reach and the desktop portal is the way out
+    pub sandboxed: bool,
+}
+
+impl Session {
+    pub(crate) fn detect() -> Self {
+        Self::from_env(|name| env::var(name).ok())
+    }
+
+    fn from_env(var: impl Fn(&str) -> Option<String>) -> Self {
+        let set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+
+        // A Wayland socket is local to the machine, over SSH only a
+        // forwarded X display can be reached
+        let graphical = set("DISPLAY") || (set("WAYLAND_DISPLAY") && !set("SSH_CONNECTION"));
+
+        let sandboxed = set("FLATPAK_ID") || set("SNAP");
+
+        let desktop = var("XDG_CURRENT_DESKTOP")
+            .and_then(|desktops| {
+                desktops
+                    .split(':')
+                    .map(desktop_from_name)
+                    .find(|desktop| *desktop != Desktop::Unknown)
+            })
+            .or_else(|| {
+                var("KDE_FULL_SESSION")
+                    .filter(|value| value == "true")
+                    .map(|_| Desktop::Kde)
+            })
+            .or_else(|| {
+                var("DESKTOP_SESSION")
+                    .map(|session| desktop_from_name(&session))
+                    .filter(|desktop| *desktop != Desktop::Unknown)
+            })
+            .unwrap_or(Desktop::Unknown);
+
+        Self {
+            desktop,
+            graphical,
+            sandboxed,
+        }
+    }
+}
+
+// Names as found in XDG_CURRENT_DESKTOP, e.g. "KDE" or "ubuntu:GNOME", and
+// in DESKTOP_SESSION, e.g. "plasmawayland" or "xubuntu"
+const DESKTOPS: &[(&str, Desktop)] = &[
+    ("kde", Desktop::Kde),
+    ("plasma", Desktop::Kde),
+    ("plasmawayland", Desktop::Kde),
+    ("plasmax11", Desktop::Kde),
+    ("kde-plasma", Desktop::Kde),
+    ("lxqt", Desktop::Lxqt),
+    ("lubuntu", Desktop::Lxqt),
+    ("mate", Desktop::Mate),
+    ("gnome", Desktop::Gtk),
+    ("gnome-xorg", Desktop::Gtk),
+    ("gnome-wayland", Desktop::Gtk),
+    ("gnome-classic", Desktop::Gtk),
+    ("gnome-flashback", Desktop::Gtk),
+    ("ubuntu", Desktop::Gtk),
+    ("ubuntu-wayland", Desktop::Gtk),
+    ("ubuntu-xorg", Desktop::Gtk),
+    ("unity", Desktop::Gtk),
+    ("xfce", Desktop::Gtk),
+    ("xubuntu", Desktop::Gtk),
+    ("x-cinnamon", Desktop::Gtk),
+    ("cinnamon", Desktop::Gtk),
+    ("cinnamon2d", Desktop::Gtk),
+    ("budgie", Desktop::Gtk),
+    ("budgie-desktop", Desktop::Gtk),
+    ("pantheon", Desktop::Gtk),
+    ("lxde", Desktop::Gtk),
+    ("lxde-pi", Desktop::Gtk),
+];
+
+// One whole name, ignoring case. DESKTOP_SESSION may also be the path of
+// the session file.
+fn desktop_from_name(name: &str) -> Desktop {
+    let name = name.trim();
+    let name = name.rsplit('/').next().unwrap_or(name);
+    DESKTOPS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(name))
+        .map_or(Desktop::Unknown, |&(_, desktop)| desktop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(vars: &[(&str, &str)]) -> Session {
+        Session::from_env(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn gnome() {
+        let gnome = session(&[
+            ("XDG_CURRENT_DESKTOP", "ubuntu:GNOME"),
+            ("WAYLAND_DISPLAY", "wayland-0"),
+        ]);
+        assert_eq!(gnome.desktop, Desktop::Gtk);
+        assert!(gnome.graphical);
+        assert!(!gnome.sandboxed);
+
+        let cinnamon = session(&[("XDG_CURRENT_DESKTOP", "X-Cinnamon"), ("DISPLAY", ":0")]);
+        assert_eq!(cinnamon.desktop, Desktop::Gtk);
+    }
+
+    #[test]
+    fn kde() {
+        for vars in [
+            [("XDG_CURRENT_DESKTOP", "KDE")],
+            [("KDE_FULL_SESSION", "true")],
+            [("DESKTOP_SESSION", "plasmawayland")],
+            [("DESKTOP_SESSION", "/usr/share/xsessions/plasma")],
+        ] {
+            assert_eq!(session(&vars).desktop, Desktop::Kde, "{:?}", vars);
+        }
+        assert_eq!(
+            session(&[("KDE_FULL_SESSION", "false")]).desktop,
+            Desktop::Unknown
+        );
+    }
+
+    #[test]
+    fn mate() {
+        assert_eq!(
+            session(&[("XDG_CURRENT_DESKTOP", "MATE")]).desktop,
+            Desktop::Mate
+        );
+        assert_eq!(
+            session(&[("DESKTOP_SESSION", "mate")]).desktop,
+            Desktop::Mate
+        );
+    }
+
+    #[test]
+    fn names_are_matched_whole() {
+        for name in ["ultimate", "kdeconnect", "gnomeish", "Hyprland", "sway", ""] {
+            assert_eq!(desktop_from_name(name), Desktop::Unknown, "{:?}", name);
+        }
+        assert_eq!(desktop_from_name(" LXQt "), Desktop::Lxqt);
+
+        // The first known entry wins, unknown ones are skipped
+        let budgie = session(&[("XDG_CURRENT_DESKTOP", "Pop:Budgie:GNOME")]);
+        assert_eq!(budgie.desktop, Desktop::Gtk);
+        let kde = session(&[
+            ("XDG_CURRENT_DESKTOP", "Hyprland"),
+            ("DESKTOP_SESSION", "plasma"),
+        ]);
+        assert_eq!(kde.desktop, Desktop::Kde);
+    }
+
+    #[test]
+    fn sandboxes() {
+        let flatpak = session(&[
+            ("FLATPAK_ID", "org.example.App"),
+            ("XDG_CURRENT_DESKTOP", "GNOME"),
+            ("WAYLAND_DISPLAY", "wayland-0"),
+        ]);
+        assert!(flatpak.sandboxed);
+        assert!(flatpak.graphical);
+
+        let snap = session(&[("SNAP", "/snap/app/42"), ("DISPLAY", ":0")]);
+        assert!(snap.sandboxed);
+
+        assert!(!session(&[("FLATPAK_ID", ""), ("SNAP", "")]).sandboxed);
+    }
+
+    #[test]
+    fn terminal_only() {
+        let console = session(&[("TERM", "linux")]);
+        assert_eq!(console.desktop, Desktop::Unknown);
+        assert!(!console.graphical);
+        assert!(!console.sandboxed);
+
+        // A Wayland socket named over SSH is the remote machine's
+        let ssh = session(&[
+            ("WAYLAND_DISPLAY", "wayland-0"),
+            ("SSH_CONNECTION", "10.0.0.2 50000 10.0.0.1 22"),
+        ]);
+        assert!(!ssh.graphical);
+        let forwarded = session(&[
+            ("DISPLAY", "localhost:10.0"),
+            ("SSH_CONNECTION", "10.0.0.2 50000 10.0.0.1 22"),
+        ]);
+        assert!(forwarded.graphical);
+
+        assert!(!session(&[("DISPLAY", "")]).graphical);
+    }
+}
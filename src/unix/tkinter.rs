@@ -0,0 +1,483 @@
+use super::*;
+use std::fmt::Write as _;
+
+// A tkinter script run by python3, for X11 machines without any dialog
+// program. Every string is written into the script as an escaped literal, so
+// nothing the caller passes can end up as code.
+pub struct Tkinter;
+
+impl Tkinter {
+    // Index of the pressed button in a window of our own, since tkinter's
+    // message boxes only come with fixed buttons
+    async fn buttons(
+        &self,
+        msg_box: &MessageBox,
+        labels: &[&str],
+    ) -> Result<Option<usize>, DialogError> {
+        let labels: Vec<String> = labels.iter().map(|label| py_str(label)).collect();
+        let script = format!(
+            "root.deiconify()\n\
+             root.title({title})\n\
+             tkinter.Label(root, text={message}, wraplength=400, justify='left')\
+             .pack(padx=20, pady=15)\n\
+             frame = tkinter.Frame(root)\n\
+             frame.pack(padx=10, pady=(0, 10))\n\
+             pressed = []\n\
+             def press(i):\n    pressed.append(i)\n    root.destroy()\n\
+             for i, label in enumerate([{labels}]):\n    \
+             tkinter.Button(frame, text=label, command=lambda i=i: press(i))\
+             .pack(side='left', padx=5)\n\
+             root.mainloop()\n\
+             if not pressed:\n    sys.exit(CANCEL)\n\
+             answer(str(pressed[0]))\n",
+            title = py_str(msg_box.title()),
+            message = py_str(msg_box.message()),
+            labels = labels.join(", "),
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer.and_then(|index| String::from_utf8_lossy(&index).parse().ok()))
+    }
+
+    // One of tkinter's fixed message boxes, which answers with a word
+    async fn ask(
+        &self,
+        msg_box: &MessageBox,
+        function: &str,
+        default: &str,
+    ) -> Result<Option<String>, DialogError> {
+        let script = format!(
+            "from tkinter import messagebox\n\
+             result = messagebox.{function}({title}, {message}, icon={icon}, default={default})\n\
+             answer({{True: 'yes', False: 'no', None: 'cancel'}}[result])\n",
+            title = py_str(msg_box.title()),
+            message = py_str(msg_box.message()),
+            icon = py_str(icon_name(msg_box.icon())),
+            default = py_str(default),
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer.map(|word| String::from_utf8_lossy(&word).into_owned()))
+    }
+}
+
+impl ProgramBackend for Tkinter {
+    fn name(&self) -> &str {
+        "tkinter"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && has_tkinter()
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("python3").await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notifications: command_exists("notify-send"),
+            notification_actions: false,
+            timeout: false,
+            parent_window: false,
+            ..Capabilities::all()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        require_display()?;
+        let function = match msg_box.icon() {
+            MessageBoxIcon::Warning => "showwarning",
+            MessageBoxIcon::Error => "showerror",
+            MessageBoxIcon::Info | MessageBoxIcon::Question => "showinfo",
+        };
+        let script = format!(
+            "from tkinter import messagebox\n\
+             messagebox.{function}({title}, {message})\n",
+            title = py_str(msg_box.title()),
+            message = py_str(msg_box.message()),
+        );
+        run_script(&script).await?;
+        Ok(())
+    }
+
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        require_display()?;
+        let default = match default {
+            OkCancel::Ok => "ok",
+            OkCancel::Cancel => "cancel",
+        };
+        Ok(
+            match self.ask(msg_box, "askokcancel", default).await?.as_deref() {
+                Some("yes") => OkCancel::Ok,
+                _ => OkCancel::Cancel,
+            },
+        )
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        require_display()?;
+        let default = match default {
+            YesNo::Yes => "yes",
+            YesNo::No => "no",
+        };
+        Ok(
+            match self.ask(msg_box, "askyesno", default).await?.as_deref() {
+                Some("yes") => YesNo::Yes,
+                _ => YesNo::No,
+            },
+        )
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        require_display()?;
+        let default = match default {
+            YesNoCancel::Yes => "yes",
+            YesNoCancel::No => "no",
+            YesNoCancel::Cancel => "cancel",
+        };
+        Ok(
+            match self
+                .ask(msg_box, "askyesnocancel", default)
+                .await?
+                .as_deref()
+            {
+                Some("yes") => YesNoCancel::Yes,
+                Some("no") => YesNoCancel::No,
+                _ => YesNoCancel::Cancel,
+            },
+        )
+    }
+
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        require_display()?;
+        let labels: Vec<&str> = msg_box.buttons().iter().map(String::as_str).collect();
+        if labels.is_empty() {
+            return self.buttons(msg_box, &["Ok"]).await;
+        }
+        self.buttons(msg_box, &labels).await
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        require_display()?;
+        let script = format!(
+            "from tkinter import simpledialog\n\
+             text = simpledialog.askstring({title}, {message}, initialvalue={default}, show={show})\n\
+             if text is None:\n    sys.exit(CANCEL)\n\
+             answer(text)\n",
+            title = py_str(input.title()),
+            message = py_str(input.message()),
+            default = py_str(input.default_value().unwrap_or("")),
+            show = if input.is_password() { "'*'" } else { "None" },
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        require_display()?;
+        let script = format!(
+            "from tkinter import filedialog\n\
+             path = filedialog.asksaveasfilename({options}, confirmoverwrite=True)\n\
+             if not path:\n    sys.exit(CANCEL)\n\
+             answer(path)\n",
+            options = file_options(dialog),
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| SaveResult::from(PathBuf::from(OsStr::from_bytes(&bytes)))))
+    }
+
+    // Several files are separated by NUL, the one byte no path contains
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        require_display()?;
+        let script = format!(
+            "from tkinter import filedialog\n\
+             if {multiple}:\n    \
+             paths = filedialog.askopenfilenames({options})\n\
+             else:\n    \
+             paths = [filedialog.askopenfilename({options})]\n\
+             paths = [path for path in paths if path]\n\
+             if not paths:\n    sys.exit(CANCEL)\n\
+             answer('\\0'.join(paths))\n",
+            multiple = if dialog.multiple_selection() {
+                "True"
+            } else {
+                "False"
+            },
+            options = file_options(dialog),
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer.filter(|bytes| !bytes.is_empty()).map(|bytes| {
+            bytes
+                .split(|&b| b == 0)
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+                .collect()
+        }))
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        require_display()?;
+        let initial_dir = if dialog.initial_path().as_os_str().is_empty() {
+            "None".to_string()
+        } else {
+            py_path(dialog.initial_path())
+        };
+        let script = format!(
+            "from tkinter import filedialog\n\
+             path = filedialog.askdirectory(title={title}, initialdir={initial_dir}, mustexist=True)\n\
+             if not path:\n    sys.exit(CANCEL)\n\
+             answer(path)\n",
+            title = py_str(dialog.title()),
+        );
+
+        let answer = run_script(&script).await?;
+        Ok(answer
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| PathBuf::from(OsStr::from_bytes(&bytes))))
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        require_display()?;
+        let script = format!(
+            "from tkinter import colorchooser\n\
+             rgb, color = colorchooser.askcolor(initialcolor={initial}, title={title})\n\
+             if color is None:\n    sys.exit(CANCEL)\n\
+             answer(color)\n",
+            initial = py_str(&rgb_to_hex(&default_rgb(chooser))),
+            title = py_str(chooser.title()),
+        );
+
+        let answer = run_script(&script).await?;
+        color_answer(
+            "python3",
+            answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+        )
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = notify_send(notification).await {
+            return result;
+        }
+
+        let msg_box = MessageBox::new(notification.title(), notification.message());
+        self.message_box_ok(&msg_box).await
+    }
+}
+
+// Exit status of a script whose dialog was cancelled. An uncaught Python
+// exception exits with 1, which is an error.
+const CANCEL_STATUS: i32 = 3;
+
+// Imports and `answer`, which prints paths as their exact bytes. tkinter's
+// main window is hidden, every dialog brings its own.
+const PROLOGUE: &str = "\
+import os
+import sys
+import tkinter
+def answer(text):
+    sys.stdout.buffer.write(os.fsencode(text))
+root = tkinter.Tk()
+root.withdraw()
+";
+
+// Runs the script with `python3 -I`, which ignores PYTHONPATH and the
+// user's site-packages
+async fn run_script(script: &str) -> Result<Option<Vec<u8>>, DialogError> {
+    let mut cmd = Command::new("python3");
+    cmd.arg("-I").arg("-c").arg(format!(
+        "{}CANCEL = {}\n{}",
+        PROLOGUE, CANCEL_STATUS, script
+    ));
+
+    let out = run_output(&mut cmd).await?;
+    match out.status.code() {
+        Some(0) => Ok(Some(out.stdout)),
+        Some(CANCEL_STATUS) => Ok(None),
+        code => Err(failed(&cmd, code, &out.stderr)),
+    }
+}
+
+// python3 may be built without Tk. Cached along with the programs, so
+// `refresh_backends` checks again.
+fn has_tkinter() -> bool {
+    const KEY: &str = "python3 tkinter";
+
+    if !command_exists("python3") {
+        return false;
+    }
+    if let Some(&found) = programs().installed.get(KEY) {
+        return found;
+    }
+
+    let found = Command::new("python3")
+        .arg("-I")
+        .arg("-c")
+        .arg("import tkinter")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    programs().installed.insert(KEY.to_string(), found);
+    found
+}
+
+fn icon_name(icon: MessageBoxIcon) -> &'static str {
+    match icon {
+        MessageBoxIcon::Info => "info",
+        MessageBoxIcon::Warning => "warning",
+        MessageBoxIcon::Error => "error",
+        MessageBoxIcon::Question => "question",
+    }
+}
+
+// Keyword arguments shared by the open and save dialogs
+fn file_options(dialog: &FileDialog) -> String {
+    let path = dialog.initial_path();
+    let (dir, file) = if path.as_os_str().is_empty() {
+        (None, None)
+    } else if path.is_dir() {
+        (Some(path), None)
+    } else {
+        (
+            path.parent().filter(|dir| !dir.as_os_str().is_empty()),
+            path.file_name(),
+        )
+    };
+
+    let mut options = format!("title={}", py_str(dialog.title()));
+    if let Some(dir) = dir {
+        let _ = write!(options, ", initialdir={}", py_path(dir));
+    }
+    if let Some(file) = file {
+        let _ = write!(options, ", initialfile={}", py_path(Path::new(file)));
+    }
+
+    // Tk starts on the first file type
+    let filetypes: Vec<String> = dialog
+        .filters_default_first()
+        .map(|filter| {
+            let patterns: Vec<String> = filter.patterns().iter().map(|p| py_str(p)).collect();
+            format!("({}, ({},))", py_str(&filter.label()), patterns.join(", "))
+        })
+        .collect();
+    if !filetypes.is_empty() {
+        let _ = write!(options, ", filetypes=[{}]", filetypes.join(", "));
+    }
+    options
+}
+
+// A Python string literal standing for `text`. Everything but letters,
+// digits and spaces is escaped, so quotes, backslashes and newlines cannot
+// end the literal.
+fn py_str(text: &str) -> String {
+    let mut literal = String::from("'");
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() || c == ' ' {
+            literal.push(c);
+        } else if (c as u32) < 0x10000 {
+            let _ = write!(literal, "\\u{:04x}", c as u32);
+        } else {
+            let _ = write!(literal, "\\U{:08x}", c as u32);
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+// The exact bytes of `path`, which need not be UTF-8
+fn py_path(path: &Path) -> String {
+    let mut literal = String::from("os.fsdecode(b'");
+    for &b in path.as_os_str().as_bytes() {
+        if b.is_ascii_alphanumeric() || b == b' ' {
+            literal.push(b as char);
+        } else {
+            let _ = write!(literal, "\\x{:02x}", b);
+        }
+    }
+    literal.push_str("')");
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn plain_text_is_kept() {
+        assert_eq!(py_str("Save 2 files"), "'Save 2 files'");
+        assert_eq!(py_str(""), "''");
+    }
+
+    #[test]
+    fn quotes_and_backslashes_are_escaped() {
+        assert_eq!(py_str("it's"), "'it\\u0027s'");
+        assert_eq!(py_str("\"a\""), "'\\u0022a\\u0022'");
+        assert_eq!(py_str("C:\\"), "'C\\u003a\\u005c'");
+        // Nothing can close the literal early
+        let literal = py_str("'); import os; ('");
+        let body = &literal[1..literal.len() - 1];
+        assert!(body
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '\\'));
+    }
+
+    #[test]
+    fn newlines_are_escaped() {
+        assert_eq!(py_str("a\nb\r\tc"), "'a\\u000ab\\u000d\\u0009c'");
+    }
+
+    #[test]
+    fn non_bmp_characters_use_long_escapes() {
+        assert_eq!(py_str("😀"), "'\\U0001f600'");
+        assert_eq!(py_str("é€"), "'\\u00e9\\u20ac'");
+    }
+
+    #[test]
+    fn paths_keep_their_bytes() {
+        assert_eq!(
+            py_path(Path::new("/tmp/a b.txt")),
+            "os.fsdecode(b'\\x2ftmp\\x2fa b\\x2etxt')"
+        );
+        assert_eq!(
+            py_path(Path::new("it's\\")),
+            "os.fsdecode(b'it\\x27s\\x5c')"
+        );
+        let latin1 = Path::new(OsStr::from_bytes(b"caf\xe9\n"));
+        assert_eq!(py_path(latin1), "os.fsdecode(b'caf\\xe9\\x0a')");
+    }
+}
@@ -0,0 +1,399 @@
+use super::*;
+use std::ffi::OsString;
+
+// whiptail(1) from newt, the dialog(1) look-alike found on minimal Debian
+// systems. It draws on the terminal and prints answers on stderr. It has no
+// file selector, so files are picked by walking directories in menus.
+pub struct Whiptail;
+
+impl Whiptail {
+    async fn yes_no(
+        &self,
+        msg_box: &MessageBox,
+        labels: Option<(&str, &str)>,
+        default_no: bool,
+    ) -> Result<bool, DialogError> {
+        let mut cmd = whiptail(&msg_box.dialog);
+        if let Some((yes, no)) = labels {
+            cmd.arg("--yes-button").arg(yes).arg("--no-button").arg(no);
+        }
+        if default_no {
+            cmd.arg("--defaultno");
+        }
+        cmd.arg("--yesno").arg(msg_box.message());
+        add_size(&mut cmd, msg_box.message(), 0);
+
+        confirm_terminal(&mut cmd).await
+    }
+
+    async fn msgbox(&self, dialog: &Dialog, text: &str) -> Result<(), DialogError> {
+        let mut cmd = whiptail(dialog);
+        cmd.arg("--msgbox").arg(text);
+        add_size(&mut cmd, text, 0);
+        confirm_terminal(&mut cmd).await?;
+        Ok(())
+    }
+
+    // Lets the user pick one of `items`, returning its index
+    async fn menu(
+        &self,
+        dialog: &Dialog,
+        text: &str,
+        items: &[String],
+        default: Option<usize>,
+    ) -> Result<Option<usize>, DialogError> {
+        let mut cmd = whiptail(dialog);
+        cmd.arg("--notags");
+        if let Some(default) = default {
+            cmd.arg("--default-item").arg((default + 1).to_string());
+        }
+
+        let rows = items.len().min(MAX_MENU_ROWS);
+        cmd.arg("--menu").arg(text);
+        add_size(&mut cmd, text, rows + 1);
+        cmd.arg(rows.to_string());
+
+        for (i, item) in items.iter().enumerate() {
+            cmd.arg((i + 1).to_string()).arg(item);
+        }
+
+        let answer = read_stderr_answer(&mut cmd).await?;
+        Ok(answer
+            .and_then(|tag| String::from_utf8_lossy(&tag).trim().parse::<usize>().ok())
+            .filter(|&n| n >= 1 && n <= items.len())
+            .map(|n| n - 1))
+    }
+
+    async fn input(
+        &self,
+        dialog: &Dialog,
+        text: &str,
+        default: Option<&str>,
+        password: bool,
+    ) -> Result<Option<String>, DialogError> {
+        let mut cmd = whiptail(dialog);
+        cmd.arg(if password {
+            "--passwordbox"
+        } else {
+            "--inputbox"
+        })
+        .arg(text);
+        add_size(&mut cmd, text, 1);
+
+        if let Some(default) = default {
+            cmd.arg(default);
+        }
+
+        let answer = read_stderr_answer(&mut cmd).await?;
+        Ok(answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    // Walks directories starting at the initial path until a file is
+    // picked, or the current directory itself when selecting a folder
+    async fn browse(
+        &self,
+        dialog: &FileDialog,
+        mode: Browse,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        let start = dialog.initial_path();
+        let (mut dir, name) = if start.as_os_str().is_empty() {
+            (PathBuf::from("."), None)
+        } else if start.is_dir() {
+            (start.to_path_buf(), None)
+        } else {
+            let parent = start.parent().filter(|p| !p.as_os_str().is_empty());
+            (
+                parent.unwrap_or(Path::new(".")).to_path_buf(),
+                start
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+            )
+        };
+
+        // Absolute, so going up ends at the root instead of piling up ".."
+        if let Ok(absolute) = dir.canonicalize() {
+            dir = absolute;
+        }
+
+        let patterns: Vec<&str> = dialog
+            .filters_default_first()
+            .next()
+            .map(|filter| filter.patterns().iter().map(String::as_str).collect())
+            .unwrap_or_default();
+
+        // Where to go back to when a directory cannot be read
+        let mut previous: Option<PathBuf> = None;
+        loop {
+            let (dirs, files) = match list_dir(&dir, &patterns) {
+                Ok(listing) => listing,
+                Err(err) => {
+                    let back = previous
+                        .take()
+                        .or_else(|| dir.parent().map(Path::to_path_buf));
+                    let Some(back) = back else {
+                        return Err(DialogError::Io(err));
+                    };
+                    let text = format!("Cannot open {}: {}", dir.display(), err);
+                    self.msgbox(&dialog.dialog, &text).await?;
+                    dir = back;
+                    continue;
+                }
+            };
+
+            let mut items = Vec::new();
+            match mode {
+                Browse::Folder => items.push("[ Select this folder ]".to_string()),
+                Browse::Save => items.push("[ New file name ]".to_string()),
+                Browse::Open => {}
+            }
+            let first_entry = items.len();
+            items.push("../".to_string());
+            items.extend(dirs.iter().map(|d| format!("{}/", d.to_string_lossy())));
+            if mode != Browse::Folder {
+                items.extend(files.iter().map(|f| f.to_string_lossy().into_owned()));
+            }
+
+            let text = dir.display().to_string();
+            let Some(picked) = self.menu(&dialog.dialog, &text, &items, None).await? else {
+                return Ok(None);
+            };
+
+            if picked < first_entry {
+                if mode == Browse::Folder {
+                    return Ok(Some(dir));
+                }
+                let prompt = format!("File name in {}:", dir.display());
+                match self
+                    .input(&dialog.dialog, &prompt, name.as_deref(), false)
+                    .await?
+                {
+                    Some(typed) if !typed.is_empty() => return Ok(Some(dir.join(typed))),
+                    _ => continue,
+                }
+            }
+
+            let entry = picked - first_entry;
+            if entry == 0 {
+                previous = Some(dir.clone());
+                dir.pop();
+            } else if entry <= dirs.len() {
+                previous = Some(dir.clone());
+                dir.push(&dirs[entry - 1]);
+            } else {
+                return Ok(Some(dir.join(&files[entry - 1 - dirs.len()])));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Browse {
+    Open,
+    Save,
+    Folder,
+}
+
+impl ProgramBackend for Whiptail {
+    fn name(&self) -> &str {
+        "whiptail"
+    }
+
+    fn is_available(&self) -> bool {
+        command_exists("whiptail")
+    }
+
+    fn uses_terminal(&self) -> bool {
+        true
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("whiptail").await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            input_box: true,
+            file_dialogs: true,
+            yes_no_cancel: true,
+            password: true,
+            filters: true,
+            notifications: command_exists("notify-send"),
+            ..Capabilities::default()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        self.msgbox(&msg_box.dialog, msg_box.message()).await
+    }
+
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        let default_no = default == OkCancel::Cancel;
+        Ok(
+            if self
+                .yes_no(msg_box, Some(("Ok", "Cancel")), default_no)
+                .await?
+            {
+                OkCancel::Ok
+            } else {
+                OkCancel::Cancel
+            },
+        )
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        Ok(if self.yes_no(msg_box, None, default == YesNo::No).await? {
+            YesNo::Yes
+        } else {
+            YesNo::No
+        })
+    }
+
+    // whiptail boxes have at most two buttons, the answers are listed in a
+    // menu whose own Cancel button also cancels
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        let answers = [YesNoCancel::Yes, YesNoCancel::No, YesNoCancel::Cancel];
+        let items = ["Yes", "No", "Cancel"].map(String::from);
+        let default = answers.iter().position(|&answer| answer == default);
+
+        let picked = self
+            .menu(&msg_box.dialog, msg_box.message(), &items, default)
+            .await?;
+        Ok(picked.map_or(YesNoCancel::Cancel, |i| answers[i]))
+    }
+
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        match msg_box.buttons() {
+            [] => self.message_box_ok(msg_box).await.map(|()| Some(0)),
+            [ok] => {
+                let mut cmd = whiptail(&msg_box.dialog);
+                cmd.arg("--ok-button")
+                    .arg(ok)
+                    .arg("--msgbox")
+                    .arg(msg_box.message());
+                add_size(&mut cmd, msg_box.message(), 0);
+                confirm_terminal(&mut cmd).await?;
+                Ok(Some(0))
+            }
+            [yes, no] => {
+                let accepted = self.yes_no(msg_box, Some((yes, no)), false).await?;
+                Ok(Some(if accepted { 0 } else { 1 }))
+            }
+            buttons => {
+                self.menu(&msg_box.dialog, msg_box.message(), buttons, None)
+                    .await
+            }
+        }
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        // whiptail ignores the initial text of password boxes
+        let default = input.default_value().filter(|_| !input.is_password());
+        self.input(&input.dialog, input.message(), default, input.is_password())
+            .await
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        Ok(self
+            .browse(dialog, Browse::Save)
+            .await?
+            .map(SaveResult::from))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        Ok(self
+            .browse(dialog, Browse::Open)
+            .await?
+            .map(|path| vec![path]))
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        self.browse(dialog, Browse::Folder).await
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        let default = rgb_to_hex(&default_rgb(chooser));
+        let answer = self
+            .input(&chooser.dialog, "Color as #rrggbb:", Some(&default), false)
+            .await?;
+        color_answer("whiptail", answer)
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        console::notify(notification).await
+    }
+}
+
+// Menus taller than this scroll
+const MAX_MENU_ROWS: usize = 12;
+
+fn whiptail(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("whiptail");
+    cmd.arg("--title").arg(dialog.title());
+    cmd
+}
+
+// whiptail does not size boxes by itself, make room for the wrapped text,
+// `rows` more lines and the buttons
+fn add_size(cmd: &mut Command, text: &str, rows: usize) {
+    let longest = text.lines().map(|line| line.chars().count()).max();
+    let width = (longest.unwrap_or(0) + 6).clamp(40, 76);
+    let lines: usize = text
+        .lines()
+        .map(|line| line.chars().count() / (width - 4) + 1)
+        .sum();
+    let height = lines + rows + 7;
+    cmd.arg(height.to_string()).arg(width.to_string());
+}
+
+// Subdirectories and the files matching any of `patterns`, sorted by name.
+// Hidden entries are left out.
+fn list_dir(dir: &Path, patterns: &[&str]) -> io::Result<(Vec<OsString>, Vec<OsString>)> {
+    let mut dirs = Vec::new();
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let shown = name.to_string_lossy();
+        if shown.starts_with('.') {
+            continue;
+        }
+
+        if entry.path().is_dir() {
+            dirs.push(name);
+        } else if patterns.is_empty() || patterns.iter().any(|p| wildcard_match(p, &shown)) {
+            files.push(name);
+        }
+    }
+
+    dirs.sort();
+    files.sort();
+    Ok((dirs, files))
+}
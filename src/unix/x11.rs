@@ -0,0 +1,681 @@
+use super::*;
+use std::mem;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyOrIdError;
+use x11rb::protocol::xproto::{
+    AtomEnum, Char2b, ClientMessageEvent, ConnectionExt as _, CreateGCAux, CreateWindowAux,
+    EventMask, Gcontext, InputFocus, KeyButMask, PropMode, Rectangle, WindowClass,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{COPY_DEPTH_FROM_PARENT, COPY_FROM_PARENT, CURRENT_TIME};
+
+// A bare message box drawn by talking the X11 protocol directly, for kiosks
+// and minimal sessions without any dialog program or toolkit. It only knows
+// message boxes, everything else is asked on the console.
+pub struct X11;
+
+impl DialogBackend for X11 {
+    fn name(&self) -> &str {
+        "x11"
+    }
+
+    fn is_available(&self) -> bool {
+        env::var_os("DISPLAY").is_some_and(|display| !display.is_empty())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            yes_no_cancel: true,
+            notifications: command_exists("notify-send"),
+            parent_window: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        message(msg_box, &["OK"], 0, Some(0))?;
+        Ok(())
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        let default = (default == OkCancel::Cancel) as usize;
+        Ok(
+            match message(msg_box, &["OK", "Cancel"], default, Some(1))? {
+                Some(0) => OkCancel::Ok,
+                _ => OkCancel::Cancel,
+            },
+        )
+    }
+
+    // There is nothing to cancel, Escape and closing the window answer No
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        let default = (default == YesNo::No) as usize;
+        Ok(match message(msg_box, &["Yes", "No"], default, Some(1))? {
+            Some(0) => YesNo::Yes,
+            _ => YesNo::No,
+        })
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        let answers = [YesNoCancel::Yes, YesNoCancel::No, YesNoCancel::Cancel];
+        let default = answers.iter().position(|&answer| answer == default);
+        let labels = ["Yes", "No", "Cancel"];
+        Ok(
+            match message(msg_box, &labels, default.unwrap_or(0), Some(2))? {
+                Some(i) => answers[i],
+                None => YesNoCancel::Cancel,
+            },
+        )
+    }
+
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        let labels: Vec<&str> = msg_box.buttons().iter().map(String::as_str).collect();
+        match labels.len() {
+            0 => self.message_box_ok(msg_box).map(|()| Some(0)),
+            1..=MAX_BUTTONS => message(msg_box, &labels, 0, None),
+            n => Err(DialogError::Unsupported(format!(
+                "{} buttons in a message box",
+                n
+            ))),
+        }
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        console::Console.input_box(input)
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        console::Console.save_file_dialog(dialog)
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        console::Console.open_file_dialog(dialog)
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        console::Console.select_folder_dialog(dialog)
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        console::Console.color_chooser_dialog(chooser)
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = async_io::block_on(notify_send(notification)) {
+            return result;
+        }
+
+        let msg_box = MessageBox::new(notification.title(), notification.message());
+        self.message_box_ok(&msg_box)
+    }
+}
+
+const MAX_BUTTONS: usize = 3;
+
+// Messages wrap at this many characters, and are cut off after this many
+// lines so that the window fits on the screen
+const MAX_COLUMNS: usize = 64;
+const MAX_LINES: usize = 40;
+
+// In pixels
+const PADDING: i16 = 16;
+const BUTTON_GAP: i16 = 10;
+const MIN_BUTTON_WIDTH: u16 = 72;
+const MIN_WINDOW_WIDTH: u16 = 240;
+
+// The core fonts every X server has, one covering Unicode and the "fixed"
+// alias for Latin-1 as the fallback
+const FONTS: [&str; 2] = [
+    "-misc-fixed-medium-r-normal--13-*-*-*-*-*-iso10646-1",
+    "fixed",
+];
+
+// From X11/keysymdef.h
+const XK_TAB: u32 = 0xff09;
+const XK_ISO_LEFT_TAB: u32 = 0xfe20;
+const XK_RETURN: u32 = 0xff0d;
+const XK_KP_ENTER: u32 = 0xff8d;
+const XK_ESCAPE: u32 = 0xff1b;
+const XK_SPACE: u32 = 0x20;
+const XK_LEFT: u32 = 0xff51;
+const XK_UP: u32 = 0xff52;
+const XK_RIGHT: u32 = 0xff53;
+const XK_DOWN: u32 = 0xff54;
+
+// Shows the message with `labels` as buttons and answers with the index of
+// the one pressed. Escape and closing the window answer `cancel`.
+fn message(
+    msg_box: &MessageBox,
+    labels: &[&str],
+    default: usize,
+    cancel: Option<usize>,
+) -> Result<Option<usize>, DialogError> {
+    let (conn, screen) = x11rb::connect(None).map_err(|_| DialogError::NoDisplay)?;
+    let conn = Arc::new(conn);
+    let window = MessageWindow::create(
+        &conn,
+        screen,
+        msg_box,
+        labels,
+        default.min(labels.len() - 1),
+    )
+    .map_err(x11_error)?;
+
+    // Closing the handle closes the window like its title bar button
+    let _hook = handle::on_cancel({
+        let conn = conn.clone();
+        let (id, protocols, delete) = (window.id, window.wm_protocols, window.wm_delete_window);
+        move || {
+            let event = ClientMessageEvent::new(32, id, protocols, [delete, 0, 0, 0, 0]);
+            let _ = conn.send_event(false, id, EventMask::NO_EVENT, event);
+            let _ = conn.flush();
+        }
+    });
+
+    let id = window.id;
+    let answer = window.run(cancel).map_err(x11_error);
+    let _ = conn.destroy_window(id);
+    let _ = conn.flush();
+    answer
+}
+
+fn x11_error(err: ReplyOrIdError) -> DialogError {
+    DialogError::Io(io::Error::other(err))
+}
+
+struct MessageWindow<'a> {
+    conn: &'a RustConnection,
+    id: u32,
+    gc: Gcontext,
+    wm_protocols: u32,
+    wm_delete_window: u32,
+    // Keysyms of every keycode from `min_keycode` on
+    keysyms: Vec<u32>,
+    keysyms_per_keycode: usize,
+    min_keycode: u8,
+    char_width: u16,
+    ascent: i16,
+    line_height: i16,
+    lines: Vec<Vec<Char2b>>,
+    buttons: Vec<Button>,
+    focus: usize,
+}
+
+struct Button {
+    label: Vec<Char2b>,
+    rect: Rectangle,
+}
+
+impl<'a> MessageWindow<'a> {
+    fn create(
+        conn: &'a RustConnection,
+        screen: usize,
+        msg_box: &MessageBox,
+        labels: &[&str],
+        focus: usize,
+    ) -> Result<Self, ReplyOrIdError> {
+        let screen = &conn.setup().roots[screen];
+
+        let font = conn.generate_id()?;
+        for name in FONTS {
+            if conn.open_font(font, name.as_bytes())?.check().is_ok() {
+                break;
+            }
+        }
+        let metrics = conn.query_font(font)?.reply()?;
+        let encode = |text: &[char]| -> Vec<Char2b> {
+            text.iter()
+                .take(255)
+                .map(|&c| {
+                    let code = u16::try_from(c as u32).unwrap_or(u16::from(b'?'));
+                    let [byte1, byte2] = code.to_be_bytes();
+                    let covered = (metrics.min_byte1..=metrics.max_byte1).contains(&byte1)
+                        && (metrics.min_char_or_byte2..=metrics.max_char_or_byte2)
+                            .contains(&u16::from(byte2));
+                    if covered {
+                        Char2b { byte1, byte2 }
+                    } else {
+                        Char2b {
+                            byte1: 0,
+                            byte2: b'?',
+                        }
+                    }
+                })
+                .collect()
+        };
+
+        // The fixed fonts are monospaced, so widths are counted in characters
+        let char_width = metrics.max_bounds.character_width.max(1) as u16;
+        let ascent = metrics.font_ascent;
+        let line_height = metrics.font_ascent + metrics.font_descent + 2;
+        let wrapped = wrap(msg_box.message(), MAX_COLUMNS, MAX_LINES);
+        let columns = wrapped.iter().map(Vec::len).max().unwrap_or(0) as u16;
+        let lines: Vec<Vec<Char2b>> = wrapped.iter().map(|line| encode(line)).collect();
+
+        let button_height = line_height as u16 + 12;
+        let mut buttons: Vec<Button> = labels
+            .iter()
+            .map(|label| {
+                let label: Vec<char> = label.chars().collect();
+                let label = encode(&label);
+                let width =
+                    (label.len() as u16 * char_width + 2 * PADDING as u16).max(MIN_BUTTON_WIDTH);
+                Button {
+                    label,
+                    rect: Rectangle {
+                        x: 0,
+                        y: 0,
+                        width,
+                        height: button_height,
+                    },
+                }
+            })
+            .collect();
+        let buttons_width: u16 = buttons.iter().map(|b| b.rect.width).sum::<u16>()
+            + (buttons.len() as u16).saturating_sub(1) * BUTTON_GAP as u16;
+
+        let width = (columns * char_width).max(buttons_width) + 2 * PADDING as u16;
+        let width = width.max(MIN_WINDOW_WIDTH);
+        let text_height = line_height.saturating_mul(lines.len() as i16);
+        let height = (3 * PADDING + text_height) as u16 + button_height;
+
+        // Buttons centered in a row under the text
+        let mut x = (width - buttons_width) as i16 / 2;
+        for button in &mut buttons {
+            button.rect.x = x;
+            button.rect.y = 2 * PADDING + text_height;
+            x += button.rect.width as i16 + BUTTON_GAP;
+        }
+
+        let id = conn.generate_id()?;
+        let x = screen.width_in_pixels.saturating_sub(width) as i16 / 2;
+        let y = screen.height_in_pixels.saturating_sub(height) as i16 / 2;
+        conn.create_window(
+            COPY_DEPTH_FROM_PARENT,
+            id,
+            screen.root,
+            x,
+            y,
+            width,
+            height,
+            0,
+            WindowClass::INPUT_OUTPUT,
+            COPY_FROM_PARENT,
+            &CreateWindowAux::new()
+                .background_pixel(screen.white_pixel)
+                .event_mask(
+                    EventMask::EXPOSURE
+                        | EventMask::KEY_PRESS
+                        | EventMask::BUTTON_PRESS
+                        | EventMask::BUTTON_RELEASE
+                        | EventMask::STRUCTURE_NOTIFY,
+                ),
+        )?;
+
+        let gc = conn.generate_id()?;
+        conn.create_gc(
+            gc,
+            id,
+            &CreateGCAux::new()
+                .foreground(screen.black_pixel)
+                .background(screen.white_pixel)
+                .font(font),
+        )?;
+        conn.close_font(font)?;
+
+        let atom = |name: &str| -> Result<u32, ReplyOrIdError> {
+            Ok(conn.intern_atom(false, name.as_bytes())?.reply()?.atom)
+        };
+        let wm_protocols = atom("WM_PROTOCOLS")?;
+        let wm_delete_window = atom("WM_DELETE_WINDOW")?;
+        conn.change_property8(
+            PropMode::REPLACE,
+            id,
+            AtomEnum::WM_NAME,
+            AtomEnum::STRING,
+            &latin1(msg_box.title()),
+        )?;
+        conn.change_property8(
+            PropMode::REPLACE,
+            id,
+            atom("_NET_WM_NAME")?,
+            atom("UTF8_STRING")?,
+            msg_box.title().as_bytes(),
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            wm_protocols,
+            AtomEnum::ATOM,
+            &[wm_delete_window],
+        )?;
+        conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            atom("_NET_WM_WINDOW_TYPE")?,
+            AtomEnum::ATOM,
+            &[atom("_NET_WM_WINDOW_TYPE_DIALOG")?],
+        )?;
+
+        // WM_NORMAL_HINTS asking for the centered position and a fixed size
+        let (p_position, p_min_size, p_max_size) = (1 << 2, 1 << 4, 1 << 5);
+        let mut hints = [0u32; 18];
+        hints[0] = p_position | p_min_size | p_max_size;
+        hints[1..5].copy_from_slice(&[x as u32, y as u32, width.into(), height.into()]);
+        hints[5..9].copy_from_slice(&[width.into(), height.into(), width.into(), height.into()]);
+        conn.change_property32(
+            PropMode::REPLACE,
+            id,
+            AtomEnum::WM_NORMAL_HINTS,
+            AtomEnum::WM_SIZE_HINTS,
+            &hints,
+        )?;
+
+        if let Some(ParentWindow::X11(parent)) = msg_box.parent() {
+            conn.change_property32(
+                PropMode::REPLACE,
+                id,
+                AtomEnum::WM_TRANSIENT_FOR,
+                AtomEnum::WINDOW,
+                &[parent as u32],
+            )?;
+        }
+
+        let setup = conn.setup();
+        let count = setup.max_keycode - setup.min_keycode + 1;
+        let mapping = conn
+            .get_keyboard_mapping(setup.min_keycode, count)?
+            .reply()?;
+
+        conn.map_window(id)?;
+        conn.flush()?;
+
+        Ok(MessageWindow {
+            conn,
+            id,
+            gc,
+            wm_protocols,
+            wm_delete_window,
+            keysyms: mapping.keysyms,
+            keysyms_per_keycode: mapping.keysyms_per_keycode.into(),
+            min_keycode: setup.min_keycode,
+            char_width,
+            ascent,
+            line_height,
+            lines,
+            buttons,
+            focus,
+        })
+    }
+
+    // Handles events until a button is pressed or the window is closed
+    fn run(mut self, cancel: Option<usize>) -> Result<Option<usize>, ReplyOrIdError> {
+        let mut pressed = None;
+        loop {
+            match self.conn.wait_for_event()? {
+                Event::Expose(event) if event.count == 0 => self.draw()?,
+                // Without a window manager nobody gives us the keyboard
+                Event::MapNotify(_) => {
+                    self.conn
+                        .set_input_focus(InputFocus::PARENT, self.id, CURRENT_TIME)?;
+                    self.conn.flush()?;
+                }
+                Event::KeyPress(event) => {
+                    let back = u16::from(event.state) & u16::from(KeyButMask::SHIFT) != 0;
+                    match self.keysym(event.detail) {
+                        XK_RETURN | XK_KP_ENTER | XK_SPACE => return Ok(Some(self.focus)),
+                        XK_ESCAPE => return Ok(cancel),
+                        XK_TAB if back => self.move_focus(false)?,
+                        XK_ISO_LEFT_TAB | XK_LEFT | XK_UP => self.move_focus(false)?,
+                        XK_TAB | XK_RIGHT | XK_DOWN => self.move_focus(true)?,
+                        _ => {}
+                    }
+                }
+                // A click counts when the mouse is released on the button
+                // it was pressed on
+                Event::ButtonPress(event) if event.detail == 1 => {
+                    pressed = self.button_at(event.event_x, event.event_y);
+                }
+                Event::ButtonRelease(event) if event.detail == 1 => {
+                    let released = self.button_at(event.event_x, event.event_y);
+                    if released.is_some() && released == pressed {
+                        return Ok(released);
+                    }
+                    pressed = None;
+                }
+                Event::ClientMessage(event)
+                    if event.type_ == self.wm_protocols
+                        && event.data.as_data32()[0] == self.wm_delete_window =>
+                {
+                    return Ok(cancel);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn keysym(&self, keycode: u8) -> u32 {
+        let index = usize::from(keycode.wrapping_sub(self.min_keycode)) * self.keysyms_per_keycode;
+        self.keysyms.get(index).copied().unwrap_or(0)
+    }
+
+    fn move_focus(&mut self, forward: bool) -> Result<(), ReplyOrIdError> {
+        let count = self.buttons.len();
+        self.focus = if forward {
+            (self.focus + 1) % count
+        } else {
+            (self.focus + count - 1) % count
+        };
+        self.draw()
+    }
+
+    fn button_at(&self, x: i16, y: i16) -> Option<usize> {
+        self.buttons.iter().position(|button| {
+            let rect = button.rect;
+            (rect.x..rect.x + rect.width as i16).contains(&x)
+                && (rect.y..rect.y + rect.height as i16).contains(&y)
+        })
+    }
+
+    fn draw(&self) -> Result<(), ReplyOrIdError> {
+        let conn = self.conn;
+        conn.clear_area(false, self.id, 0, 0, 0, 0)?;
+
+        for (line, y) in self.lines.iter().zip((0..).map(|i| i * self.line_height)) {
+            conn.image_text16(self.id, self.gc, PADDING, PADDING + self.ascent + y, line)?;
+        }
+
+        for (i, button) in self.buttons.iter().enumerate() {
+            let rect = button.rect;
+            let mut frame = vec![rect];
+            // The focused button gets a second, inner border
+            if i == self.focus {
+                frame.push(Rectangle {
+                    x: rect.x + 2,
+                    y: rect.y + 2,
+                    width: rect.width - 4,
+                    height: rect.height - 4,
+                });
+            }
+            conn.poly_rectangle(self.id, self.gc, &frame)?;
+
+            let text_width = (button.label.len() as u16 * self.char_width) as i16;
+            let x = rect.x + (rect.width as i16 - text_width) / 2;
+            let y = rect.y + (rect.height as i16 - self.line_height) / 2 + self.ascent + 1;
+            conn.image_text16(self.id, self.gc, x, y, &button.label)?;
+        }
+
+        conn.flush()?;
+        Ok(())
+    }
+}
+
+// Breaks each line of `text` between words so that none is longer than
+// `columns`, cutting words that are longer by themselves. Lines past `rows`
+// are left out, and the last one shown ends in "..." instead.
+fn wrap(text: &str, columns: usize, rows: usize) -> Vec<Vec<char>> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line: Vec<char> = Vec::new();
+        for word in paragraph.split_whitespace() {
+            let word: Vec<char> = word.chars().collect();
+            if !line.is_empty() && line.len() + 1 + word.len() > columns {
+                lines.push(mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+            while line.len() > columns {
+                let rest = line.split_off(columns);
+                lines.push(mem::replace(&mut line, rest));
+            }
+        }
+        lines.push(line);
+    }
+
+    if lines.len() > rows {
+        lines.truncate(rows);
+        if let Some(last) = lines.last_mut() {
+            last.truncate(columns.saturating_sub(3));
+            last.extend("...".chars());
+        }
+    }
+    lines
+}
+
+// WM_NAME is a Latin-1 STRING, so characters outside it become '?'. Window
+// managers that know _NET_WM_NAME show the UTF-8 title instead.
+fn latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| u8::try_from(c).unwrap_or(b'?'))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn text(lines: &[Vec<char>]) -> Vec<String> {
+        lines.iter().map(|line| line.iter().collect()).collect()
+    }
+
+    #[test]
+    fn wrap_between_words() {
+        assert_eq!(
+            text(&wrap("one two three\n\nfour", 8, 10)),
+            ["one two", "three", "", "four"]
+        );
+        assert_eq!(text(&wrap("abcdefghij", 4, 10)), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn wrap_cuts_off_long_messages() {
+        let message = "line\n".repeat(10_000);
+        let lines = text(&wrap(&message, 8, 3));
+        assert_eq!(lines, ["line", "line", "line..."]);
+        assert_eq!(text(&wrap("abcdefgh ijkl", 8, 1)), ["abcde..."]);
+    }
+
+    #[test]
+    fn latin1_title() {
+        assert_eq!(latin1("Café ✓"), b"Caf\xe9 ?");
+    }
+
+    // The first window under the root whose _NET_WM_NAME is `title`, once
+    // it shows up
+    fn find_window(conn: &RustConnection, root: u32, net_wm_name: u32, title: &str) -> u32 {
+        for _ in 0..50 {
+            let children = conn.query_tree(root).unwrap().reply().unwrap().children;
+            for window in children {
+                let name = conn
+                    .get_property(false, window, net_wm_name, AtomEnum::ANY, 0, 1024)
+                    .unwrap()
+                    .reply();
+                if name.is_ok_and(|name| name.value == title.as_bytes()) {
+                    return window;
+                }
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("no window titled {:?}", title);
+    }
+
+    // Looks at the window like a window manager would and closes it through
+    // WM_DELETE_WINDOW, which answers like Escape
+    #[test]
+    #[ignore = "needs an X server such as Xvfb"]
+    fn window_manager_protocol() {
+        let (conn, screen) = x11rb::connect(None).expect("cannot connect to the X server");
+        let root = conn.setup().roots[screen].root;
+        let atom = |name: &str| {
+            let cookie = conn.intern_atom(false, name.as_bytes()).unwrap();
+            cookie.reply().unwrap().atom
+        };
+        let previous = backend::set_thread_backend(Some(Arc::new(X11)));
+
+        let title = format!("Titre ✓ {}", std::process::id());
+        let handle = MessageBox::new(title.as_str(), "Closed by the test").spawn_yes_no(YesNo::Yes);
+        let window = find_window(&conn, root, atom("_NET_WM_NAME"), &title);
+
+        let property = |name: u32, kind: AtomEnum| {
+            let cookie = conn
+                .get_property(false, window, name, kind, 0, 1024)
+                .unwrap();
+            cookie.reply().unwrap()
+        };
+        let wm_name = property(AtomEnum::WM_NAME.into(), AtomEnum::STRING);
+        assert_eq!(
+            wm_name.value,
+            format!("Titre ? {}", std::process::id()).into_bytes()
+        );
+        assert_eq!(
+            property(atom("_NET_WM_NAME"), AtomEnum::ANY).type_,
+            atom("UTF8_STRING")
+        );
+
+        let (wm_protocols, wm_delete_window) = (atom("WM_PROTOCOLS"), atom("WM_DELETE_WINDOW"));
+        let protocols: Vec<u32> = property(wm_protocols, AtomEnum::ATOM)
+            .value32()
+            .unwrap()
+            .collect();
+        assert!(protocols.contains(&wm_delete_window));
+
+        let event =
+            ClientMessageEvent::new(32, window, wm_protocols, [wm_delete_window, 0, 0, 0, 0]);
+        conn.send_event(false, window, EventMask::NO_EVENT, event)
+            .unwrap();
+        conn.flush().unwrap();
+        assert!(matches!(handle.wait(), Ok(YesNo::No)));
+
+        // The watchdog closes the window the same way
+        let started = Instant::now();
+        let handle = MessageBox::new("tfd", "Timed out by the test")
+            .with_timeout(Duration::from_millis(500))
+            .spawn_yes_no(YesNo::Yes);
+        assert!(matches!(handle.wait(), Err(DialogError::TimedOut)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        backend::set_thread_backend(previous);
+    }
+}
@@ -0,0 +1,209 @@
+use super::*;
+
+// Xdialog takes the same boxes as dialog(1) and also prints answers on
+// stderr, but shows them as X11 windows
+pub struct Xdialog;
+
+impl Xdialog {
+    async fn yes_no(
+        &self,
+        msg_box: &MessageBox,
+        labels: Option<(&str, &str)>,
+        default_no: bool,
+    ) -> Result<bool, DialogError> {
+        require_display()?;
+        let mut cmd = xdialog(&msg_box.dialog);
+
+        if let Some((ok, cancel)) = labels {
+            cmd.arg("--ok-label")
+                .arg(ok)
+                .arg("--cancel-label")
+                .arg(cancel);
+        }
+        // Spelled with a dash unlike dialog's --defaultno
+        if default_no {
+            cmd.arg("--default-no");
+        }
+
+        cmd.arg("--yesno").arg(msg_box.message()).arg("0").arg("0");
+
+        confirm_xdialog(&mut cmd).await
+    }
+}
+
+impl ProgramBackend for Xdialog {
+    fn name(&self) -> &str {
+        "Xdialog"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && command_exists("Xdialog")
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("Xdialog").await
+    }
+
+    // Xdialog has no --timeout, it is killed once the timeout runs out, and
+    // cannot be attached to a parent window
+    fn capabilities(&self) -> Capabilities {
+        dialog::dialog_capabilities()
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        require_display()?;
+        confirm_xdialog(
+            xdialog(&msg_box.dialog)
+                .arg("--msgbox")
+                .arg(msg_box.message())
+                .arg("0")
+                .arg("0"),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        let default_no = default == OkCancel::Cancel;
+        Ok(
+            if self
+                .yes_no(msg_box, Some(("Ok", "Cancel")), default_no)
+                .await?
+            {
+                OkCancel::Ok
+            } else {
+                OkCancel::Cancel
+            },
+        )
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        Ok(if self.yes_no(msg_box, None, default == YesNo::No).await? {
+            YesNo::Yes
+        } else {
+            YesNo::No
+        })
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        // Xdialog has no third button, the answers are listed in a menu
+        require_display()?;
+        let msg_box = msg_box.clone().with_buttons(&["Yes", "No", "Cancel"]);
+        let mut cmd = xdialog(&msg_box.dialog);
+        let default_item = match default {
+            YesNoCancel::Yes => "1",
+            YesNoCancel::No => "2",
+            YesNoCancel::Cancel => "3",
+        };
+        cmd.arg("--default-item").arg(default_item);
+
+        Ok(match dialog::menu(cmd, &msg_box).await? {
+            Some(0) => YesNoCancel::Yes,
+            Some(1) => YesNoCancel::No,
+            _ => YesNoCancel::Cancel,
+        })
+    }
+
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        match msg_box.buttons() {
+            [] => self.message_box_ok(msg_box).await.map(|()| Some(0)),
+            [ok] => {
+                require_display()?;
+                confirm_xdialog(
+                    xdialog(&msg_box.dialog)
+                        .arg("--ok-label")
+                        .arg(ok)
+                        .arg("--msgbox")
+                        .arg(msg_box.message())
+                        .arg("0")
+                        .arg("0"),
+                )
+                .await?;
+                Ok(Some(0))
+            }
+            [ok, cancel] => {
+                let accepted = self.yes_no(msg_box, Some((ok, cancel)), false).await?;
+                Ok(Some(if accepted { 0 } else { 1 }))
+            }
+            _ => {
+                require_display()?;
+                dialog::menu(xdialog(&msg_box.dialog), msg_box).await
+            }
+        }
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        require_display()?;
+        dialog::input_box(xdialog(&input.dialog), input).await
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        require_display()?;
+        let path = dialog::file_select(xdialog(&dialog.dialog), dialog, "--fselect").await?;
+        Ok(path.map(SaveResult::from))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        require_display()?;
+        let path = dialog::file_select(xdialog(&dialog.dialog), dialog, "--fselect").await?;
+        Ok(path.map(|path| vec![path]))
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        require_display()?;
+        dialog::file_select(xdialog(&dialog.dialog), dialog, "--dselect").await
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        require_display()?;
+        dialog::color_input(xdialog(&chooser.dialog), chooser, "Xdialog").await
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        console::notify(notification).await
+    }
+}
+
+// Like `confirm`, but Escape and closing the window exit with 255 as in
+// dialog(1), which declines as well
+async fn confirm_xdialog(cmd: &mut Command) -> Result<bool, DialogError> {
+    let out = run_output(cmd).await?;
+    match out.status.code() {
+        Some(0) => Ok(true),
+        Some(1) | Some(255) => Ok(false),
+        code => Err(failed(cmd, code, &out.stderr)),
+    }
+}
+
+fn xdialog(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("Xdialog");
+    cmd.arg("--title").arg(dialog.title());
+    cmd
+}
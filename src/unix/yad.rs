@@ -0,0 +1,293 @@
+use super::*;
+
+// yad, a zenity fork. It takes buttons with their own exit codes, so every
+// message box is a plain dialog with the buttons we ask for.
+pub struct Yad;
+
+impl Yad {
+    // Index of the pressed button, `None` when the window was closed
+    async fn buttons(
+        &self,
+        msg_box: &MessageBox,
+        labels: &[&str],
+    ) -> Result<Option<usize>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&msg_box.dialog);
+        cmd.arg("--text")
+            .arg(msg_box.message())
+            .arg("--image")
+            .arg(icon_name(msg_box.icon()));
+
+        for (i, label) in labels.iter().enumerate() {
+            cmd.arg(format!("--button={}:{}", label, BUTTON_STATUS + i as i32));
+        }
+
+        let out = run_output(&mut cmd).await?;
+        match out.status.code() {
+            Some(CLOSED_STATUS) => Ok(None),
+            Some(code) if (BUTTON_STATUS..BUTTON_STATUS + labels.len() as i32).contains(&code) => {
+                Ok(Some((code - BUTTON_STATUS) as usize))
+            }
+            code => Err(yad_failed(&cmd, code, &out.stderr)),
+        }
+    }
+}
+
+impl ProgramBackend for Yad {
+    fn name(&self) -> &str {
+        "yad"
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && command_exists("yad")
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version("yad").await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notification_actions: false,
+            parent_window: false,
+            ..Capabilities::all()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        self.buttons(msg_box, &["Ok"]).await?;
+        Ok(())
+    }
+
+    // yad cannot move the focus to another button, so the default is not
+    // shown. Closing the window counts as the second button.
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        Ok(match self.buttons(msg_box, &["Ok", "Cancel"]).await? {
+            Some(0) => OkCancel::Ok,
+            _ => OkCancel::Cancel,
+        })
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        Ok(match self.buttons(msg_box, &["Yes", "No"]).await? {
+            Some(0) => YesNo::Yes,
+            _ => YesNo::No,
+        })
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        _default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        Ok(
+            match self.buttons(msg_box, &["Yes", "No", "Cancel"]).await? {
+                Some(0) => YesNoCancel::Yes,
+                Some(1) => YesNoCancel::No,
+                _ => YesNoCancel::Cancel,
+            },
+        )
+    }
+
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        let labels: Vec<&str> = msg_box.buttons().iter().map(String::as_str).collect();
+        if labels.is_empty() {
+            return self.buttons(msg_box, &["Ok"]).await;
+        }
+        self.buttons(msg_box, &labels).await
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&input.dialog);
+        cmd.arg("--entry").arg("--text").arg(input.message());
+
+        if let Some(default) = input.default_value().filter(|value| !value.is_empty()) {
+            cmd.arg("--entry-text").arg(default);
+        }
+
+        if input.is_password() {
+            cmd.arg("--hide-text");
+        }
+
+        let answer = read_yad_answer(&mut cmd).await?;
+        Ok(answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&dialog.dialog);
+        cmd.arg("--file").arg("--save").arg("--confirm-overwrite");
+        add_file_args(&mut cmd, dialog);
+
+        let answer = read_yad_answer(&mut cmd).await?;
+        Ok(answer
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| SaveResult::from(PathBuf::from(OsStr::from_bytes(&bytes)))))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&dialog.dialog);
+        cmd.arg("--file").arg("--add-preview");
+
+        if dialog.multiple_selection() {
+            cmd.arg("--multiple").arg("--separator=|");
+        }
+
+        add_file_args(&mut cmd, dialog);
+
+        let answer = read_yad_answer(&mut cmd).await?;
+        Ok(answer.filter(|bytes| !bytes.is_empty()).map(|bytes| {
+            bytes
+                .split(|&b| b == b'|')
+                .filter(|path| !path.is_empty())
+                .map(|path| PathBuf::from(OsStr::from_bytes(path)))
+                .collect()
+        }))
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&dialog.dialog);
+        cmd.arg("--file").arg("--directory");
+
+        if !dialog.initial_path().as_os_str().is_empty() {
+            cmd.arg("--filename").arg(dialog.initial_path());
+        }
+
+        let answer = read_yad_answer(&mut cmd).await?;
+        Ok(answer
+            .filter(|bytes| !bytes.is_empty())
+            .map(|bytes| PathBuf::from(OsStr::from_bytes(&bytes))))
+    }
+
+    // Without --alpha yad prints "#rrggbb"
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        require_display()?;
+        let mut cmd = yad(&chooser.dialog);
+        cmd.arg("--color")
+            .arg("--init-color")
+            .arg(rgb_to_hex(&default_rgb(chooser)));
+
+        let answer = read_yad_answer(&mut cmd).await?;
+        color_answer(
+            "yad",
+            answer.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()),
+        )
+    }
+
+    // The tray icon stays until it is clicked, so yad is left running.
+    // async-process reaps it once it exits.
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = notify_send(notification).await {
+            return result;
+        }
+
+        require_display()?;
+        let mut cmd = Command::new("yad");
+        cmd.arg("--notification")
+            .arg("--image")
+            .arg("dialog-information")
+            .arg("--text")
+            .arg(format!(
+                "{}: {}",
+                notification.title(),
+                notification.message()
+            ));
+
+        child_command(&cmd)
+            .kill_on_drop(false)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| spawn_error(&cmd, err))?;
+        Ok(())
+    }
+}
+
+// Exit status of the first button, the following ones count up from it.
+// Clear of yad's own 1 for errors, 70 for timeouts and 252 for closing.
+const BUTTON_STATUS: i32 = 10;
+const CLOSED_STATUS: i32 = 252;
+const YAD_TIMEOUT_STATUS: i32 = 70;
+
+fn icon_name(icon: MessageBoxIcon) -> &'static str {
+    match icon {
+        MessageBoxIcon::Info => "dialog-information",
+        MessageBoxIcon::Warning => "dialog-warning",
+        MessageBoxIcon::Error => "dialog-error",
+        MessageBoxIcon::Question => "dialog-question",
+    }
+}
+
+// Like `read_raw_answer`, yad reports Cancel as 1 and closing as 252
+async fn read_yad_answer(cmd: &mut Command) -> Result<Option<Vec<u8>>, DialogError> {
+    let out = run_output(cmd).await?;
+    match out.status.code() {
+        Some(0) => {
+            let mut stdout = out.stdout;
+            while stdout.last() == Some(&b'\n') {
+                stdout.pop();
+            }
+            Ok(Some(stdout))
+        }
+        Some(1) | Some(CLOSED_STATUS) => Ok(None),
+        code => Err(yad_failed(cmd, code, &out.stderr)),
+    }
+}
+
+fn yad_failed(cmd: &Command, code: Option<i32>, stderr: &[u8]) -> DialogError {
+    if code == Some(YAD_TIMEOUT_STATUS) && cmd.get_args().any(|arg| arg == "--timeout") {
+        return DialogError::TimedOut;
+    }
+    failed(cmd, code, stderr)
+}
+
+fn add_file_args(cmd: &mut Command, dialog: &FileDialog) {
+    if !dialog.initial_path().as_os_str().is_empty() {
+        cmd.arg("--filename").arg(dialog.initial_path());
+    }
+
+    // Same filter syntax as zenity, the first one is active
+    for filter in dialog.filters_default_first() {
+        cmd.arg(format!(
+            "--file-filter={} | {}",
+            filter.label(),
+            filter.patterns().join(" ")
+        ));
+    }
+}
+
+fn yad(dialog: &Dialog) -> Command {
+    let mut cmd = Command::new("yad");
+    cmd.arg("--title").arg(dialog.title());
+    if let Some(timeout) = dialog.timeout() {
+        cmd.arg("--timeout").arg(timeout_secs(timeout).to_string());
+    }
+    cmd
+}
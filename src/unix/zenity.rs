@@ -0,0 +1,518 @@
+use super::*;
+
+// zenity, or one of the drop-ins that take its command line
+pub struct Zenity {
+    tool: &'static Tool,
+}
+
+impl Zenity {
+    pub const ZENITY: Self = Self { tool: &TOOLS[0] };
+    pub const QARMA: Self = Self { tool: &TOOLS[1] };
+    pub const MATEDIALOG: Self = Self { tool: &TOOLS[2] };
+}
+
+// A program taking zenity's command line, and the parts of it that it
+// leaves out
+struct Tool {
+    program: &'static str,
+    // The zenity release whose options it takes, when its own version
+    // number says nothing about that
+    version: Option<Version>,
+    // --attach and --modal
+    attach: bool,
+    // An icon on message boxes
+    icon: bool,
+}
+
+const TOOLS: [Tool; 3] = [
+    Tool {
+        program: "zenity",
+        version: None,
+        attach: true,
+        icon: true,
+    },
+    // Qt rewrite for desktops without GTK
+    Tool {
+        program: "qarma",
+        version: Some(Version::Legacy),
+        attach: false,
+        icon: false,
+    },
+    // MATE's fork of zenity 3.8, numbered like MATE itself
+    Tool {
+        program: "matedialog",
+        version: Some(Version::Legacy),
+        attach: true,
+        icon: true,
+    },
+];
+
+impl Tool {
+    // Runs `<program> --version` once per process
+    async fn version(&self) -> Version {
+        match self.version {
+            Some(version) => version,
+            None => Version::parse(program_version(self.program).await.as_deref()),
+        }
+    }
+
+    // The program keeps itself above the parent window with --attach, and
+    // blocks input to it with --modal
+    fn command(&self, dialog: &Dialog) -> Command {
+        let mut cmd = Command::new(self.program);
+        if let Some(ParentWindow::X11(xid)) = dialog.parent().filter(|_| self.attach) {
+            cmd.arg(format!("--attach={}", xid)).arg("--modal");
+        }
+        if let Some(timeout) = dialog.timeout() {
+            cmd.arg(format!("--timeout={}", timeout_secs(timeout)));
+        }
+        cmd
+    }
+}
+
+impl ProgramBackend for Zenity {
+    fn name(&self) -> &str {
+        self.tool.program
+    }
+
+    fn is_available(&self) -> bool {
+        has_display() && command_exists(self.tool.program)
+    }
+
+    async fn version(&self) -> Option<String> {
+        program_version(self.tool.program).await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            notification_actions: false,
+            parent_window: self.tool.attach,
+            ..Capabilities::all()
+        }
+    }
+
+    async fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        require_display()?;
+        let mut cmd = message_box(self.tool, self.tool.version().await, msg_box, "--info");
+        confirm(&mut cmd).await?;
+        Ok(())
+    }
+
+    async fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        require_display()?;
+        let mut cmd = message_box(self.tool, self.tool.version().await, msg_box, "--question");
+        cmd.arg("--ok-label=Ok").arg("--cancel-label=Cancel");
+
+        if default == OkCancel::Cancel {
+            cmd.arg("--default-cancel");
+        }
+
+        Ok(if confirm(&mut cmd).await? {
+            OkCancel::Ok
+        } else {
+            OkCancel::Cancel
+        })
+    }
+
+    async fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        require_display()?;
+        let mut cmd = message_box(self.tool, self.tool.version().await, msg_box, "--question");
+        if default == YesNo::No {
+            cmd.arg("--default-cancel");
+        }
+
+        Ok(if confirm(&mut cmd).await? {
+            YesNo::Yes
+        } else {
+            YesNo::No
+        })
+    }
+
+    async fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        require_display()?;
+        let version = self.tool.version().await;
+        if version == Version::Legacy {
+            let mut cmd = yes_no_cancel_list(self.tool, msg_box, default);
+            let answer = read_answer(&mut cmd).await?;
+            return match answer.as_deref() {
+                Some("Yes") => Ok(YesNoCancel::Yes),
+                Some("No") => Ok(YesNoCancel::No),
+                _ => Ok(YesNoCancel::Cancel),
+            };
+        }
+
+        // Yes exits with 0, No is an extra button that prints its label and
+        // exits with 1 like Cancel and closing the window
+        let mut cmd = yes_no_cancel_question(self.tool, version, msg_box, default);
+        let out = run_output(&mut cmd).await?;
+        match out.status.code() {
+            Some(0) => Ok(YesNoCancel::Yes),
+            Some(1) if out.stdout.trim_ascii_end() == b"No" => Ok(YesNoCancel::No),
+            Some(1) => Ok(YesNoCancel::Cancel),
+            code => Err(failed(&cmd, code, &out.stderr)),
+        }
+    }
+
+    // All buttons are extra buttons, which print their label and exit
+    // with 1. Closing the window exits with 1 and prints nothing.
+    async fn message_box_buttons(
+        &self,
+        msg_box: &MessageBox,
+    ) -> Result<Option<usize>, DialogError> {
+        require_display()?;
+        let version = self.tool.version().await;
+        if version == Version::Legacy {
+            return emulate_buttons(self, msg_box).await;
+        }
+
+        let mut cmd = message_box(self.tool, version, msg_box, "--question");
+        cmd.arg("--switch");
+
+        for label in msg_box.buttons() {
+            cmd.arg("--extra-button").arg(label);
+        }
+
+        let out = run_output(&mut cmd).await?;
+        match out.status.code() {
+            Some(0) | Some(1) => {
+                let pressed = String::from_utf8_lossy(&out.stdout);
+                let pressed = pressed.trim_end_matches('\n');
+                Ok(msg_box.buttons().iter().position(|label| label == pressed))
+            }
+            code => Err(failed(&cmd, code, &out.stderr)),
+        }
+    }
+
+    async fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        require_display()?;
+        let default_value = input.default_value().unwrap_or("");
+
+        let mut cmd = self.tool.command(&input.dialog);
+        cmd.arg("--entry")
+            .arg("--title")
+            .arg(input.title())
+            .arg("--text")
+            .arg(input.message());
+
+        if !default_value.is_empty() {
+            cmd.arg("--entry-text").arg(default_value);
+        }
+
+        if input.is_password() {
+            cmd.arg("--hide-text");
+        }
+
+        read_answer(&mut cmd).await
+    }
+
+    async fn save_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<SaveResult>, DialogError> {
+        require_display()?;
+        let mut cmd = save_file_selection(self.tool, self.tool.version().await, dialog);
+        Ok(read_path(&mut cmd).await?.map(SaveResult::from))
+    }
+
+    async fn open_file_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        require_display()?;
+        let mut cmd = self.tool.command(&dialog.dialog);
+        cmd.arg("--file-selection")
+            .arg("--title")
+            .arg(dialog.title());
+
+        if dialog.multiple_selection() {
+            cmd.arg("--multiple");
+        }
+
+        add_file_args(&mut cmd, dialog);
+
+        read_paths(&mut cmd, b'|').await
+    }
+
+    async fn select_folder_dialog(
+        &self,
+        dialog: &FileDialog,
+    ) -> Result<Option<PathBuf>, DialogError> {
+        require_display()?;
+        let mut cmd = self.tool.command(&dialog.dialog);
+        cmd.arg("--file-selection")
+            .arg("--directory")
+            .arg("--title")
+            .arg(dialog.title());
+
+        if !dialog.initial_path().as_os_str().is_empty() {
+            cmd.arg("--filename").arg(dialog.initial_path());
+        }
+
+        read_path(&mut cmd).await
+    }
+
+    async fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        require_display()?;
+        let answer = read_answer(
+            self.tool
+                .command(&chooser.dialog)
+                .arg("--color-selection")
+                .arg("--title")
+                .arg(chooser.title())
+                .arg("--color")
+                .arg(rgb_to_hex(&default_rgb(chooser))),
+        )
+        .await?;
+        color_answer(self.tool.program, answer)
+    }
+
+    async fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        if let Some(result) = notify_send(notification).await {
+            return result;
+        }
+
+        require_display()?;
+        run_checked(
+            Command::new(self.tool.program)
+                .arg("--notification")
+                .arg("--text")
+                .arg(format!(
+                    "{}: {}",
+                    notification.title(),
+                    notification.message()
+                )),
+        )
+        .await
+    }
+}
+
+// zenity 4 moved to GTK4 and renamed or dropped some options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Version {
+    // zenity 3 before 3.22, without --extra-button and --switch
+    Legacy,
+    // zenity 3
+    Gtk3,
+    // zenity 4, including the 3.9x previews
+    Gtk4,
+}
+
+impl Version {
+    // Assumes the current release if the version cannot be read
+    fn parse(version: Option<&str>) -> Self {
+        let mut numbers = version
+            .unwrap_or("")
+            .split('.')
+            .map(|part| part.parse::<u32>().ok());
+
+        match (numbers.next().flatten(), numbers.next().flatten()) {
+            (Some(major), _) if major < 3 => Version::Legacy,
+            (Some(3), Some(minor)) if minor < 22 => Version::Legacy,
+            (Some(3), Some(minor)) if minor < 90 => Version::Gtk3,
+            _ => Version::Gtk4,
+        }
+    }
+
+    fn icon_option(self) -> &'static str {
+        match self {
+            Version::Legacy | Version::Gtk3 => "--icon-name",
+            Version::Gtk4 => "--icon",
+        }
+    }
+}
+
+// `--info` or `--question` box showing the title, text and icon of `msg_box`
+fn message_box(tool: &Tool, version: Version, msg_box: &MessageBox, kind: &str) -> Command {
+    let mut cmd = tool.command(&msg_box.dialog);
+    cmd.arg(kind)
+        .arg("--title")
+        .arg(msg_box.title())
+        .arg("--text")
+        .arg(msg_box.message());
+    if tool.icon {
+        cmd.arg(version.icon_option()).arg(msg_box.icon().to_str());
+    }
+    cmd
+}
+
+// A question with Yes and Cancel buttons and No as an extra button. zenity
+// can make Cancel the default but not an extra button, so a default of No
+// leaves Yes focused.
+fn yes_no_cancel_question(
+    tool: &Tool,
+    version: Version,
+    msg_box: &MessageBox,
+    default: YesNoCancel,
+) -> Command {
+    let mut cmd = message_box(tool, version, msg_box, "--question");
+    cmd.arg("--ok-label=Yes")
+        .arg("--cancel-label=Cancel")
+        .arg("--extra-button")
+        .arg("No");
+    if default == YesNoCancel::Cancel {
+        cmd.arg("--default-cancel");
+    }
+    cmd
+}
+
+// Yes, No and Cancel as a list with one of them picked, for zenity versions
+// without extra buttons
+fn yes_no_cancel_list(tool: &Tool, msg_box: &MessageBox, default: YesNoCancel) -> Command {
+    let mut cmd = tool.command(&msg_box.dialog);
+    cmd.arg("--list")
+        .arg("--radiolist")
+        .arg("--title")
+        .arg(msg_box.title())
+        .arg("--text")
+        .arg(msg_box.message())
+        .arg("--column")
+        .arg("")
+        .arg("--column")
+        .arg("Response");
+
+    for (answer, label) in [
+        (YesNoCancel::Yes, "Yes"),
+        (YesNoCancel::No, "No"),
+        (YesNoCancel::Cancel, "Cancel"),
+    ] {
+        cmd.arg(if answer == default { "TRUE" } else { "FALSE" })
+            .arg(label);
+    }
+    cmd
+}
+
+// The GTK4 file chooser always asks before overwriting and zenity 4 dropped
+// --confirm-overwrite
+fn save_file_selection(tool: &Tool, version: Version, dialog: &FileDialog) -> Command {
+    let mut cmd = tool.command(&dialog.dialog);
+    cmd.arg("--file-selection").arg("--save");
+    if version != Version::Gtk4 {
+        cmd.arg("--confirm-overwrite");
+    }
+    cmd.arg("--title").arg(dialog.title());
+
+    add_file_args(&mut cmd, dialog);
+    cmd
+}
+
+fn add_file_args(cmd: &mut Command, dialog: &FileDialog) {
+    if !dialog.initial_path().as_os_str().is_empty() {
+        cmd.arg("--filename").arg(dialog.initial_path());
+    }
+
+    // zenity starts on the first filter it is given
+    for filter in dialog.filters_default_first() {
+        cmd.arg(format!(
+            "--file-filter={} | {}",
+            filter.label(),
+            filter.patterns().join(" ")
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ZENITY: &Tool = &TOOLS[0];
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn parse_version() {
+        assert_eq!(Version::parse(Some("3.20")), Version::Legacy);
+        assert_eq!(Version::parse(Some("2.32.1")), Version::Legacy);
+        assert_eq!(Version::parse(Some("3.44.0")), Version::Gtk3);
+        assert_eq!(Version::parse(Some("3.91.0")), Version::Gtk4);
+        assert_eq!(Version::parse(Some("4.0.1")), Version::Gtk4);
+        assert_eq!(Version::parse(Some("")), Version::Gtk4);
+        assert_eq!(Version::parse(None), Version::Gtk4);
+    }
+
+    #[test]
+    fn message_box_icon() {
+        let msg_box = MessageBox::new("Title", "Text").with_icon(MessageBoxIcon::Warning);
+        let zenity3 = args(&message_box(ZENITY, Version::Gtk3, &msg_box, "--info"));
+        let zenity4 = args(&message_box(ZENITY, Version::Gtk4, &msg_box, "--info"));
+        assert_eq!(
+            zenity3,
+            [
+                "--info",
+                "--title",
+                "Title",
+                "--text",
+                "Text",
+                "--icon-name",
+                "warning"
+            ]
+        );
+        assert_eq!(
+            zenity4,
+            ["--info", "--title", "Title", "--text", "Text", "--icon", "warning"]
+        );
+    }
+
+    #[test]
+    fn save_confirms_overwrite_before_zenity_4() {
+        let dialog = FileDialog::new("Save");
+        let zenity3 = args(&save_file_selection(ZENITY, Version::Gtk3, &dialog));
+        let zenity4 = args(&save_file_selection(ZENITY, Version::Gtk4, &dialog));
+        assert_eq!(
+            zenity3,
+            [
+                "--file-selection",
+                "--save",
+                "--confirm-overwrite",
+                "--title",
+                "Save"
+            ]
+        );
+        assert_eq!(zenity4, ["--file-selection", "--save", "--title", "Save"]);
+    }
+
+    #[test]
+    fn yes_no_cancel_radiolist() {
+        let msg_box = MessageBox::new("Title", "Text");
+        let list = args(&yes_no_cancel_list(ZENITY, &msg_box, YesNoCancel::No));
+        assert_eq!(
+            list,
+            [
+                "--list",
+                "--radiolist",
+                "--title",
+                "Title",
+                "--text",
+                "Text",
+                "--column",
+                "",
+                "--column",
+                "Response",
+                "FALSE",
+                "Yes",
+                "TRUE",
+                "No",
+                "FALSE",
+                "Cancel",
+            ]
+        );
+    }
+}
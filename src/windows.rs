@@ -5,6 +5,7 @@ use std::mem;
 use std::os::windows::ffi::{OsStrExt, OsStringExt};
 use std::path::Path;
 use std::ptr;
+use std::sync::Arc;
 use ::windows::core::HSTRING;
 use ::windows::Data::Xml::Dom::XmlDocument;
 use ::windows::Win32::UI::Shell::GetCurrentProcessExplicitAppUserModelID;
@@ -45,6 +46,50 @@ struct OPENFILENAMEW {
     FlagsEx: u32,
 }
 
+// Task dialog structures are byte-packed in commctrl.h
+#[allow(non_snake_case)]
+#[repr(C, packed(1))]
+struct TASKDIALOG_BUTTON {
+    nButtonID: i32,
+    pszButtonText: *const u16,
+}
+
+#[allow(non_snake_case)]
+#[repr(C, packed(1))]
+struct TASKDIALOGCONFIG {
+    cbSize: u32,
+    hwndParent: HWND,
+    hInstance: HINSTANCE,
+    dwFlags: u32,
+    dwCommonButtons: u32,
+    pszWindowTitle: *const u16,
+    pszMainIcon: *const u16,
+    pszMainInstruction: *const u16,
+    pszContent: *const u16,
+    cButtons: u32,
+    pButtons: *const TASKDIALOG_BUTTON,
+    nDefaultButton: i32,
+    cRadioButtons: u32,
+    pRadioButtons: *const TASKDIALOG_BUTTON,
+    nDefaultRadioButton: i32,
+    pszVerificationText: *const u16,
+    pszExpandedInformation: *const u16,
+    pszExpandedControlText: *const u16,
+    pszCollapsedControlText: *const u16,
+    pszFooterIcon: *const u16,
+    pszFooter: *const u16,
+    pfCallback: *const std::ffi::c_void,
+    lpCallbackData: isize,
+    cxWidth: u32,
+}
+
+type TaskDialogIndirectFn = unsafe extern "system" fn(
+    pTaskConfig: *const TASKDIALOGCONFIG,
+    pnButton: *mut i32,
+    pnRadioButton: *mut i32,
+    pfVerificationFlagChecked: *mut i32,
+) -> i32;
+
 #[allow(non_snake_case)]
 #[repr(C)]
 struct BROWSEINFOW {
@@ -139,6 +184,15 @@ const IDCANCEL: i32 = 2;
 const IDYES: i32 = 6;
 const IDNO: i32 = 7;
 
+const WM_CLOSE: u32 = 0x0010;
+
+const TDF_ALLOW_DIALOG_CANCELLATION: u32 = 0x0008;
+const TD_WARNING_ICON: u16 = 0xFFFF;
+const TD_ERROR_ICON: u16 = 0xFFFE;
+const TD_INFORMATION_ICON: u16 = 0xFFFD;
+// Custom task dialog buttons get IDs from here on, clear of IDOK and friends
+const FIRST_BUTTON_ID: i32 = 100;
+
 /* */
 extern "system" {
     fn MessageBoxW(hwnd: HWND, text: *const u16, caption: *const u16, utype: u32) -> i32;
@@ -147,9 +201,19 @@ extern "system" {
     fn SHBrowseForFolderW(lpbi: *mut BROWSEINFOW) -> PIDLIST_ABSOLUTE;
     fn SHGetPathFromIDListW(pidl: PIDLIST_ABSOLUTE, pszPath: *mut u16) -> i32;
     fn ChooseColorW(lpcc: *mut CHOOSECOLORW) -> i32;
+    fn CommDlgExtendedError() -> u32;
     fn CoTaskMemFree(pv: *mut std::ffi::c_void);
     fn LoadIconW(hInstance: HINSTANCE, lpIconName: *const u16) -> HICON;
     fn Shell_NotifyIconW(dwMessage: u32, lpdata: *mut NOTIFYICONDATAW) -> i32;
+    fn GetCurrentThreadId() -> u32;
+    fn EnumThreadWindows(
+        dwThreadId: u32,
+        lpfn: unsafe extern "system" fn(HWND, isize) -> i32,
+        lParam: isize,
+    ) -> i32;
+    fn PostMessageW(hwnd: HWND, msg: u32, wparam: usize, lparam: isize) -> i32;
+    fn LoadLibraryW(lpLibFileName: *const u16) -> HINSTANCE;
+    fn GetProcAddress(hModule: HINSTANCE, lpProcName: *const u8) -> *const std::ffi::c_void;
 }
 
 fn to_wstring(s: &str) -> Vec<u16> {
@@ -162,7 +226,53 @@ fn from_wstring(s: &[u16]) -> String {
     os_string.to_string_lossy().into_owned()
 }
 
-pub fn message_box_ok(msg_box: &MessageBox) {
+// File names may hold unpaired surrogates, keep them as they are
+fn path_to_wstring(path: &Path) -> Vec<u16> {
+    path.as_os_str().encode_wide().chain(once(0)).collect()
+}
+
+fn path_from_wstring(s: &[u16]) -> PathBuf {
+    let len = s.iter().position(|&c| c == 0).unwrap_or(s.len());
+    PathBuf::from(OsString::from_wide(&s[..len]))
+}
+
+fn owner(parent: Option<ParentWindow>) -> HWND {
+    match parent {
+        Some(ParentWindow::Win32(hwnd)) => HWND(hwnd),
+        _ => HWND(0),
+    }
+}
+
+// MessageBoxW returns 0 when the box could not be created
+fn message_box_failed() -> DialogError {
+    DialogError::Failed {
+        program: "MessageBoxW".to_string(),
+        code: io::Error::last_os_error().raw_os_error(),
+        message: String::new(),
+    }
+}
+
+// Common dialogs return 0 both when cancelled and when they fail
+fn common_dialog_result<T>(
+    program: &str,
+    result: i32,
+    value: impl FnOnce() -> T,
+) -> Result<Option<T>, DialogError> {
+    if result != 0 {
+        return Ok(Some(value()));
+    }
+
+    match unsafe { CommDlgExtendedError() } {
+        0 => Ok(None),
+        code => Err(DialogError::Failed {
+            program: program.to_string(),
+            code: Some(code as i32),
+            message: String::new(),
+        }),
+    }
+}
+
+pub fn message_box_ok(msg_box: &MessageBox) -> Result<(), DialogError> {
     let title = msg_box.dialog.title();
     let message = msg_box.dialog.message();
     let icon = msg_box.icon();
@@ -177,17 +287,25 @@ pub fn message_box_ok(msg_box: &MessageBox) {
         MessageBoxIcon::Question => MB_ICONQUESTION,
     };
 
-    unsafe {
+    let result = unsafe {
         MessageBoxW(
-            HWND(0),
+            owner(msg_box.dialog.parent()),
             w_message.as_ptr(),
             w_title.as_ptr(),
             MB_OK | icon_flag,
-        );
+        )
+    };
+
+    match result {
+        0 => Err(message_box_failed()),
+        _ => Ok(()),
     }
 }
 
-pub fn message_box_ok_cancel(msg_box: &MessageBox, default: OkCancel) -> OkCancel {
+pub fn message_box_ok_cancel(
+    msg_box: &MessageBox,
+    default: OkCancel,
+) -> Result<OkCancel, DialogError> {
     let title = msg_box.dialog.title();
     let message = msg_box.dialog.message();
     let icon = msg_box.icon();
@@ -209,7 +327,7 @@ pub fn message_box_ok_cancel(msg_box: &MessageBox, default: OkCancel) -> OkCance
 
     let result = unsafe {
         MessageBoxW(
-            HWND(0),
+            owner(msg_box.dialog.parent()),
             w_message.as_ptr(),
             w_title.as_ptr(),
             MB_OKCANCEL | icon_flag | default_button,
@@ -217,12 +335,13 @@ pub fn message_box_ok_cancel(msg_box: &MessageBox, default: OkCancel) -> OkCance
     };
 
     match result {
-        IDOK => OkCancel::Ok,
-        _ => OkCancel::Cancel,
+        0 => Err(message_box_failed()),
+        IDOK => Ok(OkCancel::Ok),
+        _ => Ok(OkCancel::Cancel),
     }
 }
 
-pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> YesNo {
+pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> Result<YesNo, DialogError> {
     let title = msg_box.dialog.title();
     let message = msg_box.dialog.message();
     let icon = msg_box.icon();
@@ -244,7 +363,7 @@ pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> YesNo {
 
     let result = unsafe {
         MessageBoxW(
-            HWND(0),
+            owner(msg_box.dialog.parent()),
             w_message.as_ptr(),
             w_title.as_ptr(),
             MB_YESNO | icon_flag | default_button,
@@ -252,12 +371,16 @@ pub fn message_box_yes_no(msg_box: &MessageBox, default: YesNo) -> YesNo {
     };
 
     match result {
-        IDYES => YesNo::Yes,
-        _ => YesNo::No,
+        0 => Err(message_box_failed()),
+        IDYES => Ok(YesNo::Yes),
+        _ => Ok(YesNo::No),
     }
 }
 
-pub fn message_box_yes_no_cancel(msg_box: &MessageBox, default: YesNoCancel) -> YesNoCancel {
+pub fn message_box_yes_no_cancel(
+    msg_box: &MessageBox,
+    default: YesNoCancel,
+) -> Result<YesNoCancel, DialogError> {
     let title = msg_box.dialog.title();
     let message = msg_box.dialog.message();
     let icon = msg_box.icon();
@@ -280,7 +403,7 @@ pub fn message_box_yes_no_cancel(msg_box: &MessageBox, default: YesNoCancel) ->
 
     let result = unsafe {
         MessageBoxW(
-            HWND(0),
+            owner(msg_box.dialog.parent()),
             w_message.as_ptr(),
             w_title.as_ptr(),
             MB_YESNOCANCEL | icon_flag | default_button,
@@ -288,13 +411,88 @@ pub fn message_box_yes_no_cancel(msg_box: &MessageBox, default: YesNoCancel) ->
     };
 
     match result {
-        IDYES => YesNoCancel::Yes,
-        IDNO => YesNoCancel::No,
-        _ => YesNoCancel::Cancel,
+        0 => Err(message_box_failed()),
+        IDYES => Ok(YesNoCancel::Yes),
+        IDNO => Ok(YesNoCancel::No),
+        _ => Ok(YesNoCancel::Cancel),
     }
 }
 
-pub fn input_box(input: &InputBox) -> Option<String> {
+// TaskDialogIndirect only exists in version 6 of comctl32, which is loaded
+// when the application's manifest asks for it
+fn task_dialog_indirect() -> Option<TaskDialogIndirectFn> {
+    let library = to_wstring("comctl32.dll");
+    unsafe {
+        let module = LoadLibraryW(library.as_ptr());
+        if module.is_null() {
+            return None;
+        }
+
+        let function = GetProcAddress(module, b"TaskDialogIndirect\0".as_ptr());
+        if function.is_null() {
+            None
+        } else {
+            Some(mem::transmute::<*const std::ffi::c_void, TaskDialogIndirectFn>(function))
+        }
+    }
+}
+
+// Shows the custom buttons in a task dialog, `None` if there are none or
+// task dialogs are not available
+fn task_dialog(msg_box: &MessageBox) -> Option<Result<Option<usize>, DialogError>> {
+    if msg_box.buttons().is_empty() {
+        return None;
+    }
+    let task_dialog_indirect = task_dialog_indirect()?;
+
+    let w_title = to_wstring(msg_box.dialog.title());
+    let w_message = to_wstring(msg_box.dialog.message());
+    let w_labels: Vec<Vec<u16>> = msg_box.buttons().iter().map(|l| to_wstring(l)).collect();
+    let buttons: Vec<TASKDIALOG_BUTTON> = w_labels
+        .iter()
+        .enumerate()
+        .map(|(i, label)| TASKDIALOG_BUTTON {
+            nButtonID: FIRST_BUTTON_ID + i as i32,
+            pszButtonText: label.as_ptr(),
+        })
+        .collect();
+
+    let icon = match msg_box.icon() {
+        MessageBoxIcon::Warning => TD_WARNING_ICON,
+        MessageBoxIcon::Error => TD_ERROR_ICON,
+        MessageBoxIcon::Info | MessageBoxIcon::Question => TD_INFORMATION_ICON,
+    };
+
+    let mut config: TASKDIALOGCONFIG = unsafe { mem::zeroed() };
+    config.cbSize = mem::size_of::<TASKDIALOGCONFIG>() as u32;
+    config.hwndParent = owner(msg_box.dialog.parent());
+    config.dwFlags = TDF_ALLOW_DIALOG_CANCELLATION;
+    config.pszWindowTitle = w_title.as_ptr();
+    config.pszMainIcon = icon as usize as *const u16;
+    config.pszContent = w_message.as_ptr();
+    config.cButtons = buttons.len() as u32;
+    config.pButtons = buttons.as_ptr();
+    config.nDefaultButton = FIRST_BUTTON_ID;
+
+    let mut pressed = 0;
+    let result = unsafe {
+        task_dialog_indirect(&config, &mut pressed, ptr::null_mut(), ptr::null_mut())
+    };
+
+    Some(if result < 0 {
+        Err(DialogError::Failed {
+            program: "TaskDialogIndirect".to_string(),
+            code: Some(result),
+            message: String::new(),
+        })
+    } else if pressed >= FIRST_BUTTON_ID {
+        Ok(Some((pressed - FIRST_BUTTON_ID) as usize))
+    } else {
+        Ok(None)
+    })
+}
+
+pub fn input_box(input: &InputBox) -> Result<Option<String>, DialogError> {
     // For Windows, we'll use a simple message box for now
     // Note: in a real implementation, we should create a proper input dialog
     // This is a basic implementation that shows the prompt and returns the default value
@@ -314,7 +512,7 @@ pub fn input_box(input: &InputBox) -> Option<String> {
 
     let result = unsafe {
         MessageBoxW(
-            HWND(0),
+            owner(input.dialog.parent()),
             w_message.as_ptr(),
             w_title.as_ptr(),
             MB_OKCANCEL | MB_ICONQUESTION,
@@ -322,54 +520,53 @@ pub fn input_box(input: &InputBox) -> Option<String> {
     };
 
     match result {
-        IDOK => Some(default.to_string()),
-        _ => None,
+        0 => Err(message_box_failed()),
+        IDOK => Ok(Some(default.to_string())),
+        _ => Ok(None),
     }
 }
 
-pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
-    let title = dialog.dialog.title();
-    let path = dialog.path();
-    let filter_patterns = dialog.filter_patterns();
-    let description = dialog.filter_description();
-
-    let w_title = to_wstring(title);
-
-    // Build filter string
+// "description\0pattern;pattern\0" per group, then "All Files" and a
+// second NUL ending the list
+fn filter_string(filters: &[FileFilter]) -> String {
     let mut filter = String::new();
-    if !description.is_empty() && !filter_patterns.is_empty() {
-        filter.push_str(description);
+    for group in filters {
+        filter.push_str(&group.label());
         filter.push('\0');
-
-        for (i, pattern) in filter_patterns.iter().enumerate() {
-            if i > 0 {
-                filter.push(';');
-            }
-            filter.push_str(pattern);
-        }
+        filter.push_str(&group.patterns().join(";"));
         filter.push('\0');
     }
 
-    // Add "All Files" filter
     filter.push_str("All Files\0*.*\0\0");
-    let w_filter = to_wstring(&filter);
+    filter
+}
+
+pub fn save_file_dialog(dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+    let title = dialog.dialog.title();
+    let path = dialog.initial_path();
+    let filters = dialog.filters();
+
+    let w_title = to_wstring(title);
+
+    let w_filter = to_wstring(&filter_string(filters));
 
     // Prepare buffer for file name
     let mut buffer = vec![0u16; 260]; // MAX_PATH
-    if !path.is_empty() {
-        let path_w = to_wstring(path);
+    if !path.as_os_str().is_empty() {
+        let path_w = path_to_wstring(path);
         let len = path_w.len().min(buffer.len() - 1);
         buffer[..len].copy_from_slice(&path_w[..len]);
     }
 
     let mut ofn: OPENFILENAMEW = unsafe { mem::zeroed() };
     ofn.lStructSize = mem::size_of::<OPENFILENAMEW>() as u32;
-    ofn.hwndOwner = ptr::null_mut();
-    ofn.lpstrFilter = if filter_patterns.is_empty() {
+    ofn.hwndOwner = owner(dialog.dialog.parent()).0 as *mut std::ffi::c_void;
+    ofn.lpstrFilter = if filters.is_empty() {
         ptr::null()
     } else {
         w_filter.as_ptr()
     };
+    ofn.nFilterIndex = dialog.default_filter() as u32 + 1;
     ofn.lpstrFile = buffer.as_mut_ptr();
     ofn.nMaxFile = buffer.len() as u32;
     ofn.lpstrTitle = w_title.as_ptr();
@@ -377,57 +574,43 @@ pub fn save_file_dialog(dialog: &FileDialog) -> Option<String> {
 
     let result = unsafe { GetSaveFileNameW(&mut ofn) };
 
-    if result != 0 {
-        Some(from_wstring(&buffer))
-    } else {
-        None
-    }
+    // nFilterIndex now holds the group the user picked, 1-based and past
+    // the end of `filters` for the "All Files" group from `filter_string`
+    common_dialog_result("GetSaveFileNameW", result, || SaveResult {
+        path: path_from_wstring(&buffer),
+        filter_index: (ofn.nFilterIndex as usize)
+            .checked_sub(1)
+            .filter(|&i| i < filters.len()),
+    })
 }
 
-pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
+pub fn open_file_dialog(dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
     let title = dialog.dialog.title();
-    let path = dialog.path();
-    let filter_patterns = dialog.filter_patterns();
-    let description = dialog.filter_description();
+    let path = dialog.initial_path();
+    let filters = dialog.filters();
     let allow_multi = dialog.multiple_selection();
 
     let w_title = to_wstring(title);
 
-    // Build filter string
-    let mut filter = String::new();
-    if !description.is_empty() && !filter_patterns.is_empty() {
-        filter.push_str(description);
-        filter.push('\0');
-
-        for (i, pattern) in filter_patterns.iter().enumerate() {
-            if i > 0 {
-                filter.push(';');
-            }
-            filter.push_str(pattern);
-        }
-        filter.push('\0');
-    }
-
-    // Add "All Files" filter
-    filter.push_str("All Files\0*.*\0\0");
-    let w_filter = to_wstring(&filter);
+    let w_filter = to_wstring(&filter_string(filters));
 
     // Prepare buffer for file name(s)
     let mut buffer = vec![0u16; 32768]; // Large buffer for multiple files
-    if !path.is_empty() {
-        let path_w = to_wstring(path);
+    if !path.as_os_str().is_empty() {
+        let path_w = path_to_wstring(path);
         let len = path_w.len().min(buffer.len() - 1);
         buffer[..len].copy_from_slice(&path_w[..len]);
     }
 
     let mut ofn: OPENFILENAMEW = unsafe { mem::zeroed() };
     ofn.lStructSize = mem::size_of::<OPENFILENAMEW>() as u32;
-    ofn.hwndOwner = ptr::null_mut();
-    ofn.lpstrFilter = if filter_patterns.is_empty() {
+    ofn.hwndOwner = owner(dialog.dialog.parent()).0 as *mut std::ffi::c_void;
+    ofn.lpstrFilter = if filters.is_empty() {
         ptr::null()
     } else {
         w_filter.as_ptr()
     };
+    ofn.nFilterIndex = dialog.default_filter() as u32 + 1;
     ofn.lpstrFile = buffer.as_mut_ptr();
     ofn.nMaxFile = buffer.len() as u32;
     ofn.lpstrTitle = w_title.as_ptr();
@@ -439,14 +622,14 @@ pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
 
     let result = unsafe { GetOpenFileNameW(&mut ofn) };
 
-    if result != 0 {
+    common_dialog_result("GetOpenFileNameW", result, || {
         if allow_multi {
             let mut files = Vec::new();
             let mut start = 0;
 
             // First part is the directory
-            let dir = from_wstring(&buffer[start..]);
-            start += dir.len() + 1;
+            let dir = path_from_wstring(&buffer[start..]);
+            start += dir.as_os_str().encode_wide().count() + 1;
 
             if buffer[start] == 0 {
                 // Only one file selected
@@ -454,35 +637,31 @@ pub fn open_file_dialog(dialog: &FileDialog) -> Option<Vec<String>> {
             } else {
                 // Multiple files, directory followed by filenames
                 while start < buffer.len() && buffer[start] != 0 {
-                    let filename = from_wstring(&buffer[start..]);
-                    if filename.is_empty() {
+                    let filename = path_from_wstring(&buffer[start..]);
+                    if filename.as_os_str().is_empty() {
                         break;
                     }
 
-                    let path = Path::new(&dir).join(&filename);
-                    files.push(path.to_string_lossy().into_owned());
-
-                    start += filename.len() + 1;
+                    start += filename.as_os_str().encode_wide().count() + 1;
+                    files.push(dir.join(filename));
                 }
             }
 
-            Some(files)
+            files
         } else {
-            Some(vec![from_wstring(&buffer)])
+            vec![path_from_wstring(&buffer)]
         }
-    } else {
-        None
-    }
+    })
 }
 
-pub fn select_folder_dialog(dialog: &FileDialog) -> Option<String> {
+pub fn select_folder_dialog(dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
     let title = dialog.dialog.title();
-    let path = dialog.path();
+    let path = dialog.initial_path();
 
     let w_title = to_wstring(title);
 
     let mut bi: BROWSEINFOW = unsafe { mem::zeroed() };
-    bi.hwndOwner = ptr::null_mut();
+    bi.hwndOwner = owner(dialog.dialog.parent()).0 as *mut std::ffi::c_void;
     bi.lpszTitle = w_title.as_ptr();
     bi.ulFlags = BIF_RETURNONLYFSDIRS | BIF_NEWDIALOGSTYLE;
 
@@ -494,16 +673,22 @@ pub fn select_folder_dialog(dialog: &FileDialog) -> Option<String> {
         unsafe { CoTaskMemFree(pidl) };
 
         if result != 0 {
-            Some(from_wstring(&buffer))
+            Ok(Some(path_from_wstring(&buffer)))
         } else {
-            None
+            Err(DialogError::Failed {
+                program: "SHGetPathFromIDListW".to_string(),
+                code: None,
+                message: String::new(),
+            })
         }
     } else {
-        None
+        Ok(None)
     }
 }
 
-pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])> {
+pub fn color_chooser_dialog(
+    chooser: &ColorChooser,
+) -> Result<Option<(String, [u8; 3])>, DialogError> {
     let title = chooser.dialog.title();
 
     let w_title = to_wstring(title);
@@ -521,14 +706,14 @@ pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])>
 
     let mut cc: CHOOSECOLORW = unsafe { mem::zeroed() };
     cc.lStructSize = mem::size_of::<CHOOSECOLORW>() as u32;
-    cc.hwndOwner = ptr::null_mut();
+    cc.hwndOwner = owner(chooser.dialog.parent()).0 as *mut std::ffi::c_void;
     cc.rgbResult = rgb_value;
     cc.lpCustColors = custom_colors.as_mut_ptr();
     cc.Flags = CC_RGBINIT | CC_FULLOPEN | CC_ANYCOLOR;
 
     let result = unsafe { ChooseColorW(&mut cc) };
 
-    if result != 0 {
+    common_dialog_result("ChooseColorW", result, || {
         let r = (cc.rgbResult & 0xFF) as u8;
         let g = ((cc.rgbResult >> 8) & 0xFF) as u8;
         let b = ((cc.rgbResult >> 16) & 0xFF) as u8;
@@ -536,13 +721,11 @@ pub fn color_chooser_dialog(chooser: &ColorChooser) -> Option<(String, [u8; 3])>
         let rgb = [r, g, b];
         let hex = super::rgb_to_hex(&rgb);
 
-        Some((hex, rgb))
-    } else {
-        None
-    }
+        (hex, rgb)
+    })
 }
 
-pub fn notification(notification: &Notification) -> bool {
+pub fn notification(notification: &Notification) -> Result<(), DialogError> {
     /*
     if is_windows10_or_newer() {
         match show_toast_notification(notification) {
@@ -572,7 +755,7 @@ fn is_windows10_or_newer() -> bool {
     false
 }
 
-fn show_legacy_notification(notification: &Notification) -> bool {
+fn show_legacy_notification(notification: &Notification) -> Result<(), DialogError> {
     let title = to_wstring(notification.title());
     let message = to_wstring(notification.message());
     
@@ -585,7 +768,131 @@ fn show_legacy_notification(notification: &Notification) -> bool {
         )
     };
     
-    result == IDOK
+    match result {
+        0 => Err(message_box_failed()),
+        _ => Ok(()),
+    }
+}
+
+unsafe extern "system" fn collect_window(hwnd: HWND, lparam: isize) -> i32 {
+    (*(lparam as *mut Vec<isize>)).push(hwnd.0);
+    1
+}
+
+// Top-level windows of a thread
+fn thread_windows(thread_id: u32) -> Vec<isize> {
+    let mut windows: Vec<isize> = Vec::new();
+    unsafe {
+        EnumThreadWindows(thread_id, collect_window, &mut windows as *mut Vec<isize> as isize);
+    }
+    windows
+}
+
+// Native dialogs run a modal loop on the calling thread, closing them
+// through a DialogHandle or a timeout posts WM_CLOSE to the windows that
+// thread opened since. Dialogs with a timeout run on the caller's thread,
+// which may be the application's UI thread, so its own windows stay open.
+fn closable<T>(show: impl FnOnce() -> Result<T, DialogError>) -> Result<T, DialogError> {
+    let thread_id = unsafe { GetCurrentThreadId() };
+    let existing = thread_windows(thread_id);
+    let _hook = handle::on_cancel(move || {
+        for hwnd in thread_windows(thread_id) {
+            if !existing.contains(&hwnd) {
+                unsafe { PostMessageW(HWND(hwnd), WM_CLOSE, 0, 0) };
+            }
+        }
+    });
+    show()
+}
+
+// Win32 message boxes and common dialogs
+pub struct Win32;
+
+impl DialogBackend for Win32 {
+    fn name(&self) -> &str {
+        "win32"
+    }
+
+    // The input box only shows the prompt and answers with the default,
+    // notifications are message boxes
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            file_dialogs: true,
+            yes_no_cancel: true,
+            multiple_selection: true,
+            color_chooser: true,
+            filters: true,
+            parent_window: true,
+            ..Capabilities::default()
+        }
+    }
+
+    fn message_box_ok(&self, msg_box: &MessageBox) -> Result<(), DialogError> {
+        closable(|| message_box_ok(msg_box))
+    }
+
+    fn message_box_ok_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: OkCancel,
+    ) -> Result<OkCancel, DialogError> {
+        closable(|| message_box_ok_cancel(msg_box, default))
+    }
+
+    fn message_box_yes_no(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNo,
+    ) -> Result<YesNo, DialogError> {
+        closable(|| message_box_yes_no(msg_box, default))
+    }
+
+    fn message_box_yes_no_cancel(
+        &self,
+        msg_box: &MessageBox,
+        default: YesNoCancel,
+    ) -> Result<YesNoCancel, DialogError> {
+        closable(|| message_box_yes_no_cancel(msg_box, default))
+    }
+
+    // Task dialogs where available, the fixed message boxes otherwise
+    fn message_box_buttons(&self, msg_box: &MessageBox) -> Result<Option<usize>, DialogError> {
+        closable(|| match task_dialog(msg_box) {
+            Some(result) => result,
+            None => backend::emulate_buttons(self, msg_box),
+        })
+    }
+
+    fn input_box(&self, input: &InputBox) -> Result<Option<String>, DialogError> {
+        closable(|| input_box(input))
+    }
+
+    fn save_file_dialog(&self, dialog: &FileDialog) -> Result<Option<SaveResult>, DialogError> {
+        closable(|| save_file_dialog(dialog))
+    }
+
+    fn open_file_dialog(&self, dialog: &FileDialog) -> Result<Option<Vec<PathBuf>>, DialogError> {
+        closable(|| open_file_dialog(dialog))
+    }
+
+    fn select_folder_dialog(&self, dialog: &FileDialog) -> Result<Option<PathBuf>, DialogError> {
+        closable(|| select_folder_dialog(dialog))
+    }
+
+    fn color_chooser_dialog(
+        &self,
+        chooser: &ColorChooser,
+    ) -> Result<Option<(String, [u8; 3])>, DialogError> {
+        closable(|| color_chooser_dialog(chooser))
+    }
+
+    fn notification(&self, notification: &Notification) -> Result<(), DialogError> {
+        self::notification(notification)
+    }
+}
+
+pub fn builtin_backends() -> Vec<Arc<dyn DialogBackend>> {
+    vec![Arc::new(Win32)]
 }
 
 /*